@@ -0,0 +1,158 @@
+use enumset::EnumSet;
+use minsweeper_rs::board::{BoardSize, ConventionalSize, Point};
+use minsweeper_rs::minsweeper::{generate_game, generate_solvable_game, SetMinsweeperGame};
+use minsweeper_rs::solver::mia::{Level, LogicCounts, MiaLogic, MiaSolver};
+use minsweeper_rs::solver::sat::SatSolver;
+use minsweeper_rs::solver::start::{SafeStart, ZeroStart};
+use minsweeper_rs::solver::{GameResult, Solver};
+use minsweeper_rs::{GameState, Minsweeper};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+// plays the same boards with every solver and prints a table to stderr and a json summary to stdout
+//
+//     cargo run --release --example bench -- [games per size] [--start any|safe|zero]
+//
+// the first click is always the middle of the board, `--start` picks which boards are allowed under it
+
+type Play = Box<dyn Fn(&mut dyn Minsweeper) -> (GameResult, LogicCounts)>;
+
+struct Entry {
+    name: &'static str,
+    play: Play
+}
+
+impl Entry {
+    fn mia(name: &'static str, solver: MiaSolver) -> Self {
+        Self { name, play: Box::new(move |game| solver.solve_game_counted(game)) }
+    }
+
+    // anything that isn't mia doesn't say which `MiaLogic` it used
+    fn other(name: &'static str, solver: impl Solver + 'static) -> Self {
+        Self { name, play: Box::new(move |game| (solver.solve_game(game), LogicCounts::default())) }
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    won: usize,
+    lost: usize,
+    resigned: usize,
+    time: Duration,
+    logic: LogicCounts
+}
+
+impl Tally {
+    fn games(&self) -> usize {
+        self.won + self.lost + self.resigned
+    }
+
+    fn rate(&self, count: usize) -> f64 {
+        count as f64 / self.games().max(1) as f64
+    }
+
+    fn average_ms(&self) -> f64 {
+        self.time.as_secs_f64() * 1000.0 / self.games().max(1) as f64
+    }
+}
+
+fn main() {
+    let mut games = 100;
+    let mut start = "safe".to_string();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--start" => start = args.next().expect("--start needs one of any, safe or zero"),
+            games_arg => games = games_arg.parse().expect("the number of games should be a number")
+        }
+    }
+
+    let generate: fn(BoardSize, Point) -> GameState = match start.as_str() {
+        "any" => |size, _| generate_game(size),
+        "safe" => |size, point| generate_solvable_game(size, &SafeStart, point),
+        "zero" => |size, point| generate_solvable_game(size, &ZeroStart, point),
+        other => panic!("unknown start {other}, expected any, safe or zero")
+    };
+
+    let entries = [
+        Entry::mia("mia beginner", MiaSolver::skill(Level::Beginner)),
+        Entry::mia("mia intermediate", MiaSolver::skill(Level::Intermediate)),
+        Entry::mia("mia advanced", MiaSolver::skill(Level::Advanced)),
+        Entry::mia("mia expert", MiaSolver::skill(Level::Expert)),
+        Entry::other("sat", SatSolver),
+        Entry::other("safe start", SafeStart),
+        Entry::other("zero start", ZeroStart)
+    ];
+
+    let mut results = vec![];
+
+    for size in [ConventionalSize::Beginner, ConventionalSize::Intermediate, ConventionalSize::Expert] {
+        let board_size = size.size();
+        let point = (board_size.width().get() / 2, board_size.height().get() / 2);
+
+        let mut tallies: Vec<Tally> = entries.iter()
+                .map(|_| Tally::default())
+                .collect();
+
+        for _ in 0..games {
+            let state = generate(board_size, point);
+
+            for (entry, tally) in entries.iter().zip(&mut tallies) {
+                let mut game = SetMinsweeperGame::new(state.clone());
+                let _ = Minsweeper::reveal(&mut game, point);
+
+                let now = Instant::now();
+                let (result, logic) = (entry.play)(&mut game);
+                tally.time += now.elapsed();
+
+                match result {
+                    GameResult::Won => tally.won += 1,
+                    GameResult::Lost => tally.lost += 1,
+                    GameResult::Resigned => tally.resigned += 1
+                }
+                tally.logic.merge(&logic);
+            }
+        }
+
+        for (entry, tally) in entries.iter().zip(tallies) {
+            results.push((size, entry.name, tally));
+        }
+    }
+
+    eprintln!("{:<13} {:<17} {:>7} {:>7} {:>8} {:>9}  logic", "size", "solver", "won", "lost", "resigned", "avg ms");
+    for (size, name, tally) in &results {
+        let logic: Vec<_> = EnumSet::<MiaLogic>::all().iter()
+                .filter(|e| tally.logic.get(*e) > 0)
+                .map(|e| format!("{e:?} {:.1}%", tally.logic.get(e) as f64 * 100.0 / tally.logic.total() as f64))
+                .collect();
+
+        eprintln!("{:<13} {:<17} {:>6.1}% {:>6.1}% {:>7.1}% {:>9.3}  {}",
+            format!("{size:?}"), name,
+            tally.rate(tally.won) * 100.0, tally.rate(tally.lost) * 100.0, tally.rate(tally.resigned) * 100.0,
+            tally.average_ms(), if logic.is_empty() { "-".to_string() } else { logic.join(", ") });
+    }
+
+    let summary = json!({
+        "games": games,
+        "start": start,
+        "results": results.iter()
+                .map(|(size, name, tally)| json!({
+                    "size": format!("{size:?}"),
+                    "solver": name,
+                    "won": tally.won,
+                    "lost": tally.lost,
+                    "resigned": tally.resigned,
+                    "win_rate": tally.rate(tally.won),
+                    "loss_rate": tally.rate(tally.lost),
+                    "resign_rate": tally.rate(tally.resigned),
+                    "average_ms": tally.average_ms(),
+                    "logic": tally.logic.iter()
+                            .map(|(logic, count)| (format!("{logic:?}"), json!(count)))
+                            .collect::<serde_json::Map<_, _>>()
+                }))
+                .collect::<Vec<_>>()
+    });
+
+    println!("{summary:#}");
+}
@@ -0,0 +1,355 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use minsweeper_rs::board::{Board, BoardSize, Point};
+use minsweeper_rs::minsweeper::{generate_game, generate_solvable_game, SetMinsweeperGame};
+use minsweeper_rs::solver::mia::{Level, LogicCounts, MiaSolver};
+use minsweeper_rs::solver::sat::SatSolver;
+use minsweeper_rs::solver::start::{SafeStart, WinStart, ZeroStart};
+use minsweeper_rs::solver::{GameResult, Move, Solver};
+use minsweeper_rs::{Cell, CellState, CellType, GameState, GameStatus, Minsweeper};
+use serde_json::json;
+use std::error::Error;
+use std::io::Read;
+use std::path::PathBuf;
+
+// boards are read either as json (a serialised `GameState`) or as the text `Board` prints:
+// a layout like ` 1*1` where nothing has been revealed yet, or a player's view like `▩1!1`
+// where `▩` is unknown, `!` is flagged and every number (or space) showing has been revealed
+
+#[derive(Parser)]
+#[command(name = "minsweeper", about = "generate, solve and rate minsweeper boards")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(Subcommand)]
+enum Command {
+    // clap picks its help text up from doc comments, which is why these few have them
+    /// Generate boards, one after another separated by blank lines (or one json object per line)
+    Generate {
+        /// beginner, intermediate, expert or WIDTHxHEIGHTxMINES
+        #[arg(long, default_value = "expert")]
+        size: BoardSize,
+        #[arg(long)]
+        seed: Option<u64>,
+        /// the first click, defaults to the middle of the board
+        #[arg(long, value_parser = parse_point)]
+        start: Option<Point>,
+        /// only keep boards this solver wins from the first click
+        #[arg(long, value_enum, default_value_t = SolverName::None)]
+        solver: SolverName,
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format
+    },
+    /// Print the next move a solver would make on a position and why
+    Solve {
+        /// reads from stdin when left out
+        file: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = SolverName::Mia)]
+        solver: SolverName,
+        /// how many mines the whole board has, only needed when the position is a player's view in text
+        #[arg(long)]
+        mines: Option<usize>,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format
+    },
+    /// Play boards with mia from the first click and report their 3BV and which logic it took
+    Rate {
+        file: Option<PathBuf>,
+        #[arg(long, value_parser = parse_point)]
+        start: Option<Point>,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format
+    },
+    /// Run a solver over every board from the first click and count the results
+    PlayBatch {
+        file: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = SolverName::Mia)]
+        solver: SolverName,
+        #[arg(long, value_parser = parse_point)]
+        start: Option<Point>,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum Format {
+    Text,
+    Json
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum SolverName {
+    None,
+    Safe,
+    Zero,
+    Win,
+    MiaBeginner,
+    MiaIntermediate,
+    MiaAdvanced,
+    Mia,
+    Sat
+}
+
+impl SolverName {
+    fn solver(self) -> Option<Box<dyn Solver>> {
+        Some(match self {
+            SolverName::None => return None,
+            SolverName::Safe => Box::new(SafeStart),
+            SolverName::Zero => Box::new(ZeroStart),
+            SolverName::Win => Box::new(WinStart),
+            SolverName::MiaBeginner => Box::new(MiaSolver::skill(Level::Beginner)),
+            SolverName::MiaIntermediate => Box::new(MiaSolver::skill(Level::Intermediate)),
+            SolverName::MiaAdvanced => Box::new(MiaSolver::skill(Level::Advanced)),
+            SolverName::Mia => Box::new(MiaSolver::skill(Level::Expert)),
+            SolverName::Sat => Box::new(SatSolver)
+        })
+    }
+}
+
+fn main() {
+    if let Err(error) = run(Cli::parse().command) {
+        eprintln!("{error}");
+        std::process::exit(1)
+    }
+}
+
+fn run(command: Command) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Generate { size, seed, start, solver, count, format } => {
+            if let Some(seed) = seed {
+                fastrand::seed(seed);
+            }
+
+            let start = start.unwrap_or_else(|| middle(size));
+            if start.0 >= size.width().get() || start.1 >= size.height().get() {
+                return Err(format!("{start:?} isn't on a {size:?} board").into())
+            }
+
+            let solver = solver.solver();
+
+            for i in 0..count {
+                let state = match &solver {
+                    Some(solver) => generate_solvable_game(size, solver, start),
+                    None => generate_game(size)
+                };
+
+                match format {
+                    Format::Text if i > 0 => print!("\n{}", state.board),
+                    Format::Text => print!("{}", state.board),
+                    Format::Json => println!("{}", serde_json::to_string(&state)?)
+                }
+            }
+        }
+        Command::Solve { file, solver, mines, format } => {
+            let [state] = &read_boards(file, mines)?[..] else {
+                return Err("solve takes exactly one position".into())
+            };
+            let solver = solver.solver()
+                    .ok_or("solve needs a solver")?;
+
+            // the solver only ever gets to see what a player could
+            let view = SetMinsweeperGame::new(state.clone())
+                    .gamestate()
+                    .clone();
+
+            let Some(Move { actions, reason }) = solver.solve(&view) else {
+                match format {
+                    Format::Text => println!("no move"),
+                    Format::Json => println!("null")
+                }
+                return Ok(())
+            };
+
+            let mut actions: Vec<_> = actions.into_iter().collect();
+            actions.sort_by_key(|e| (e.point.1, e.point.0));
+
+            match format {
+                Format::Text => {
+                    for action in &actions {
+                        println!("{:?} {} {}", action.operation, action.point.0, action.point.1);
+                    }
+                    if let Some(reason) = reason {
+                        println!("because {}", reason.logic);
+                    }
+                }
+                Format::Json => println!("{}", json!({
+                    "actions": actions,
+                    "logic": reason.as_ref().map(|e| format!("{:?}", e.logic)),
+                    "reason": reason.as_ref().map(|e| e.logic.to_string()),
+                    "related": reason.map(|e| e.related).unwrap_or_default()
+                }))
+            }
+        }
+        Command::Rate { file, start, format } => {
+            for state in read_layouts(file)? {
+                let start = start.unwrap_or_else(|| middle(state.board.size()));
+
+                let mut game = SetMinsweeperGame::new(state.clone());
+                let (result, counts) = match game.reveal(start) {
+                    Ok(_) => MiaSolver::default().solve_game_counted(&mut game),
+                    Err(_) => return Err(format!("couldn't reveal {start:?}").into())
+                };
+
+                let board = solved(&state);
+
+                match format {
+                    Format::Text => println!("3bv {} openings {} {:?} {}",
+                        board.bbbv(), board.openings(), result, describe(&counts)),
+                    Format::Json => println!("{}", json!({
+                        "bbbv": board.bbbv(),
+                        "openings": board.openings(),
+                        "result": result,
+                        "logic": counts.iter()
+                                .map(|(logic, count)| (format!("{logic:?}"), json!(count)))
+                                .collect::<serde_json::Map<_, _>>()
+                    }))
+                }
+            }
+        }
+        Command::PlayBatch { file, solver, start, format } => {
+            let solver = solver.solver()
+                    .ok_or("play-batch needs a solver")?;
+            let (mut won, mut lost, mut resigned) = (0, 0, 0);
+
+            for state in read_layouts(file)? {
+                let start = start.unwrap_or_else(|| middle(state.board.size()));
+
+                let mut game = SetMinsweeperGame::new(state);
+                let result = match game.reveal(start) {
+                    Ok(_) => solver.solve_game(&mut game),
+                    Err(_) => return Err(format!("couldn't reveal {start:?}").into())
+                };
+
+                match result {
+                    GameResult::Won => won += 1,
+                    GameResult::Lost => lost += 1,
+                    GameResult::Resigned => resigned += 1
+                }
+            }
+
+            match format {
+                Format::Text => println!("won {won} lost {lost} resigned {resigned}"),
+                Format::Json => println!("{}", json!({ "won": won, "lost": lost, "resigned": resigned }))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn middle(size: BoardSize) -> Point {
+    (size.width().get() / 2, size.height().get() / 2)
+}
+
+fn parse_point(text: &str) -> Result<Point, String> {
+    let (x, y) = text.split_once(',')
+            .ok_or_else(|| format!("{text:?} should look like x,y"))?;
+
+    Ok((
+        x.trim().parse().map_err(|_| format!("{x:?} isn't a number"))?,
+        y.trim().parse().map_err(|_| format!("{y:?} isn't a number"))?
+    ))
+}
+
+// the layout with every safe cell showing, which is what 3bv and openings are counted on
+fn solved(state: &GameState) -> Board {
+    let mut board = state.board.clone();
+    for cell in board.iter_mut() {
+        if cell.cell_type != CellType::Mine {
+            cell.cell_state = CellState::Revealed;
+        }
+    }
+    board
+}
+
+fn describe(counts: &LogicCounts) -> String {
+    let mut counts: Vec<_> = counts.iter()
+            .map(|(logic, count)| format!("{logic:?} {count}"))
+            .collect();
+    counts.sort();
+    counts.join(", ")
+}
+
+// playing a board needs to know where every mine is, which a player's view doesn't
+fn read_layouts(file: Option<PathBuf>) -> Result<Vec<GameState>, Box<dyn Error>> {
+    let states = read_boards(file, None)?;
+
+    if states.iter().any(|state| state.board.iter().any(|e| e.cell_type == CellType::Unknown)) {
+        return Err("this needs the whole layout, not a player's view".into())
+    }
+
+    Ok(states)
+}
+
+fn read_boards(file: Option<PathBuf>, mines: Option<usize>) -> Result<Vec<GameState>, Box<dyn Error>> {
+    let text = match file {
+        Some(file) => std::fs::read_to_string(file)?,
+        None => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        }
+    };
+
+    if text.trim_start().starts_with('{') {
+        return text.lines()
+                .filter(|e| !e.trim().is_empty())
+                .map(|e| Ok(serde_json::from_str(e)?))
+                .collect()
+    }
+
+    // boards are separated by empty lines, `lines` takes care of any \r\n
+    let lines: Vec<&str> = text.lines().collect();
+    lines.split(|e| e.is_empty())
+            .filter(|e| !e.is_empty())
+            .map(|e| parse_board(e, mines))
+            .collect()
+}
+
+fn parse_board(lines: &[&str], mines: Option<usize>) -> Result<GameState, Box<dyn Error>> {
+    let rows: Vec<Vec<char>> = lines.iter()
+            .map(|e| e.chars().collect())
+            .collect();
+
+    let height = rows.len();
+    let width = rows[0].len();
+    if rows.iter().any(|e| e.len() != width) {
+        return Err("every row of a board should be the same length".into())
+    }
+
+    let is_view = rows.iter()
+            .flatten()
+            .any(|e| matches!(e, '▩' | '!' | '?'));
+    let shown = if is_view { CellState::Revealed } else { CellState::Unknown };
+
+    let mines = match is_view {
+        true => mines.ok_or("a player's view doesn't say how many mines there are, pass --mines")?,
+        false => rows.iter().flatten().filter(|e| **e == '*').count()
+    };
+    let flags = rows.iter().flatten().filter(|e| **e == '!').count();
+
+    let size = BoardSize::new(width, height, mines)?;
+    let mut board = Board::empty(size);
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            board[(x, y)] = match cell {
+                ' ' => Cell::new(CellType::EMPTY, shown),
+                '*' => Cell::new(CellType::Mine, CellState::Unknown),
+                '▩' => Cell::new(CellType::Unknown, CellState::Unknown),
+                '!' => Cell::new(CellType::Unknown, CellState::Flagged),
+                '?' => Cell::new(CellType::Unknown, CellState::Revealed),
+                number => match number.to_digit(10) {
+                    Some(number) if number <= 8 => Cell::new(CellType::Safe(number as u8), shown),
+                    _ => return Err(format!("{number:?} isn't a cell").into())
+                }
+            }
+        }
+    }
+
+    Ok(GameState::new(GameStatus::Playing, board, mines as isize - flags as isize))
+}
@@ -0,0 +1,273 @@
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{event, execute, queue, terminal};
+use minsweeper_rs::board::{BoardSize, ConventionalSize, Point};
+use minsweeper_rs::minsweeper::{Hint, MinsweeperGame};
+use minsweeper_rs::solver::mia::MiaSolver;
+use minsweeper_rs::solver::start::{SafeStart, ZeroStart};
+use minsweeper_rs::solver::{Operation, Solver};
+use minsweeper_rs::{CellState, CellType, GameStatus, Minsweeper};
+use std::io::{stdout, Stdout, Write};
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "usage: minsweeper-tui [beginner|intermediate|expert|WIDTHxHEIGHTxMINES] [--start any|safe|zero|solvable] [--no-hints]";
+
+// which solver (if any) the first click's board has to satisfy
+#[derive(Copy, Clone, Debug)]
+enum Start {
+    Any,
+    Safe,
+    Zero,
+    Solvable
+}
+
+impl Start {
+    fn solver(self) -> Option<Box<dyn Solver>> {
+        match self {
+            Start::Any => None,
+            Start::Safe => Some(Box::new(SafeStart)),
+            Start::Zero => Some(Box::new(ZeroStart)),
+            Start::Solvable => Some(Box::new(MiaSolver::default()))
+        }
+    }
+}
+
+struct App {
+    game: MinsweeperGame,
+    size: BoardSize,
+    start: Start,
+    hints: bool,
+    cursor: Point,
+    started: Option<Instant>,
+    finished: Option<Duration>,
+    hint: Option<Hint>,
+    message: String
+}
+
+impl App {
+    fn new(size: BoardSize, start: Start, hints: bool) -> Self {
+        let mut app = Self {
+            game: MinsweeperGame::new(size, Box::new(|| {}), Box::new(|| {})),
+            size,
+            start,
+            hints,
+            cursor: (0, 0),
+            started: None,
+            finished: None,
+            hint: None,
+            message: String::new()
+        };
+        app.restart(size);
+        app
+    }
+
+    fn restart(&mut self, size: BoardSize) {
+        if size != self.size {
+            self.game = MinsweeperGame::new(size, Box::new(|| {}), Box::new(|| {}));
+            self.size = size;
+        }
+
+        match self.start.solver() {
+            Some(solver) => self.game.start_with_solver(solver),
+            None => self.game.start()
+        };
+
+        self.cursor = (size.width().get() / 2, size.height().get() / 2);
+        self.started = None;
+        self.finished = None;
+        self.hint = None;
+        self.message.clear();
+    }
+
+    fn elapsed(&self) -> Duration {
+        match (self.started, self.finished) {
+            (_, Some(finished)) => finished,
+            (Some(started), None) => started.elapsed(),
+            (None, None) => Duration::ZERO
+        }
+    }
+
+    fn status(&self) -> GameStatus {
+        self.game.gamestate().status
+    }
+
+    fn act(&mut self, act: impl FnOnce(&mut MinsweeperGame, Point) -> bool) {
+        if !self.status().is_ongoing() {
+            return
+        }
+
+        self.hint = None;
+        self.message.clear();
+
+        if act(&mut self.game, self.cursor) && self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+
+        if self.status().is_over() {
+            self.finished = Some(self.elapsed());
+        }
+    }
+
+    fn ask_for_hint(&mut self) {
+        if !self.hints || self.started.is_none() || self.status() != GameStatus::Playing {
+            return
+        }
+
+        self.hint = self.game.hint();
+        self.message = match &self.hint {
+            Some(hint) => hint.explanation.clone(),
+            None => "no idea, you'll have to guess".to_string()
+        };
+    }
+
+    fn handle(&mut self, key: KeyEvent) -> bool {
+        let (x, y) = self.cursor;
+        let (width, height) = (self.size.width().get(), self.size.height().get());
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Left | KeyCode::Char('h') => self.cursor = (x.saturating_sub(1), y),
+            KeyCode::Right | KeyCode::Char('l') => self.cursor = ((x + 1).min(width - 1), y),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = (x, y.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor = (x, (y + 1).min(height - 1)),
+            KeyCode::Char(' ') | KeyCode::Enter => self.act(|game, point| game.left_click(point).is_ok()),
+            KeyCode::Char('c') => self.act(|game, point| game.clear_around(point).is_ok()),
+            KeyCode::Char('f') => self.act(|game, point| {
+                let _ = game.toggle_flag(point);
+                false
+            }),
+            KeyCode::Char('?') => self.ask_for_hint(),
+            KeyCode::Char('n') => self.restart(self.size),
+            KeyCode::Char('1') => self.restart(ConventionalSize::Beginner.size()),
+            KeyCode::Char('2') => self.restart(ConventionalSize::Intermediate.size()),
+            KeyCode::Char('3') => self.restart(ConventionalSize::Expert.size()),
+            _ => {}
+        }
+
+        true
+    }
+
+    fn draw(&self, out: &mut Stdout) -> std::io::Result<()> {
+        let state = self.game.gamestate();
+
+        let status = match state.status {
+            GameStatus::AwaitingFirstClick => "pick somewhere to start",
+            GameStatus::Won => "you win :3",
+            GameStatus::Lost => "you lose :(",
+            GameStatus::NotStarted | GameStatus::Playing => ""
+        };
+
+        queue!(out,
+            MoveTo(0, 0),
+            Print(format!("mines {:<4} time {:<5} {}", state.remaining_mines, self.elapsed().as_secs(), status)),
+            Clear(ClearType::UntilNewLine)
+        )?;
+
+        for y in 0..self.size.height().get() {
+            queue!(out, MoveTo(0, y as u16 + 2))?;
+
+            for x in 0..self.size.width().get() {
+                let point = (x, y);
+                let cell = state.board[point];
+
+                let colour = match self.hint.as_ref().and_then(|e| e.actions.iter().find(|e| e.point == point)) {
+                    Some(action) if action.operation == Operation::Flag => Color::Red,
+                    Some(_) => Color::Green,
+                    None if self.hint.as_ref().is_some_and(|e| e.related.contains(&point)) => Color::Yellow,
+                    None => match (cell.cell_type, cell.cell_state) {
+                        (CellType::Safe(1), CellState::Revealed) => Color::Blue,
+                        (CellType::Safe(2), CellState::Revealed) => Color::DarkGreen,
+                        (CellType::Safe(3), CellState::Revealed) => Color::DarkRed,
+                        (CellType::Safe(4), CellState::Revealed) => Color::DarkBlue,
+                        (CellType::Safe(5), CellState::Revealed) => Color::DarkYellow,
+                        (CellType::Safe(6), CellState::Revealed) => Color::DarkCyan,
+                        (CellType::Mine, _) | (_, CellState::Flagged) => Color::Red,
+                        _ => Color::Reset
+                    }
+                };
+
+                queue!(out, SetForegroundColor(colour))?;
+                if point == self.cursor {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+                queue!(out, Print(cell), SetAttribute(Attribute::Reset), ResetColor, Print(" "))?;
+            }
+
+            queue!(out, Clear(ClearType::UntilNewLine))?;
+        }
+
+        let bottom = self.size.height().get() as u16 + 3;
+        let keys = if self.hints { "arrows/hjkl move  space reveal  f flag  c chord  ? hint  n new  1/2/3 size  q quit" }
+                else { "arrows/hjkl move  space reveal  f flag  c chord  n new  1/2/3 size  q quit" };
+
+        queue!(out,
+            MoveTo(0, bottom), Print(&self.message), Clear(ClearType::UntilNewLine),
+            MoveTo(0, bottom + 1), Print(keys), Clear(ClearType::FromCursorDown)
+        )?;
+
+        out.flush()
+    }
+}
+
+fn main() {
+    let mut size = ConventionalSize::Beginner.size();
+    let mut start = Start::Safe;
+    let mut hints = true;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--start" => start = match args.next().as_deref() {
+                Some("any") => Start::Any,
+                Some("safe") => Start::Safe,
+                Some("zero") => Start::Zero,
+                Some("solvable") => Start::Solvable,
+                _ => exit(USAGE)
+            },
+            "--no-hints" => hints = false,
+            "--help" | "-h" => exit(USAGE),
+            arg => size = arg.parse().unwrap_or_else(|e| exit(&format!("{e}\n{USAGE}")))
+        }
+    }
+
+    let mut app = App::new(size, start, hints);
+
+    let mut out = stdout();
+    terminal::enable_raw_mode().expect("terminal should support raw mode");
+    execute!(out, EnterAlternateScreen, Hide).expect("should be able to set up the terminal");
+
+    let result = run(&mut app, &mut out);
+
+    let _ = execute!(out, Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+
+    if let Err(error) = result {
+        exit(&error.to_string())
+    }
+}
+
+fn run(app: &mut App, out: &mut Stdout) -> std::io::Result<()> {
+    queue!(out, Clear(ClearType::All))?;
+
+    loop {
+        app.draw(out)?;
+
+        // wake up every so often even without input so the timer keeps ticking
+        if !event::poll(Duration::from_millis(250))? {
+            continue
+        }
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press && !app.handle(key) => return Ok(()),
+            Event::Resize(_, _) => queue!(out, Clear(ClearType::All))?,
+            _ => {}
+        }
+    }
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{message}");
+    std::process::exit(2)
+}
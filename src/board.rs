@@ -4,9 +4,10 @@ use std::fmt::{Display, Formatter};
 use std::iter::Flatten;
 use std::num::NonZeroUsize;
 use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 use std::vec::IntoIter;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Board {
     grid: Vec<Vec<Cell>>,
@@ -65,6 +66,43 @@ impl Board {
         board
     }
 
+    // both of these only look at the numbers, so they work on a fully revealed board as well as on the real layout
+    pub fn openings(&self) -> usize {
+        let mut seen = vec![vec![false; self.size.height().into()]; self.size.width().into()];
+        let mut openings = 0;
+
+        for point in self.size.points() {
+            if seen[point.0][point.1] || self[point].cell_type != CellType::EMPTY {
+                continue
+            }
+
+            seen[point.0][point.1] = true;
+            openings += 1;
+            let mut stack = vec![point];
+
+            while let Some(point) = stack.pop() {
+                for neighbour in self.size.neighbours(point) {
+                    if !seen[neighbour.0][neighbour.1] && self[neighbour].cell_type == CellType::EMPTY {
+                        seen[neighbour.0][neighbour.1] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        openings
+    }
+
+    pub fn bbbv(&self) -> usize {
+        let isolated = self.size.points()
+                .filter(|point| matches!(self[*point].cell_type, CellType::Safe(number) if number > 0))
+                .filter(|point| !self.size.neighbours(*point)
+                        .any(|e| self[e].cell_type == CellType::EMPTY))
+                .count();
+
+        self.openings() + isolated
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Cell> {
         self.into_iter()
     }
@@ -161,6 +199,24 @@ impl Display for BoardSizeError {
 
 impl Error for BoardSizeError {}
 
+#[derive(Debug)]
+pub enum ParseBoardSizeError {
+    Malformed(String),
+    Invalid(BoardSizeError)
+}
+
+impl Display for ParseBoardSizeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseBoardSizeError::Malformed(text) =>
+                write!(f, "{:?} isn't beginner, intermediate, expert or WIDTHxHEIGHTxMINES", text),
+            ParseBoardSizeError::Invalid(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl Error for ParseBoardSizeError {}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoardSize {
@@ -241,4 +297,40 @@ impl ConventionalSize {
             ConventionalSize::Expert => BoardSize::new(30, 16, 99).unwrap()
         }
     }
+}
+
+impl FromStr for ConventionalSize {
+    type Err = ParseBoardSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "beginner" => Ok(ConventionalSize::Beginner),
+            "intermediate" => Ok(ConventionalSize::Intermediate),
+            "expert" => Ok(ConventionalSize::Expert),
+            _ => Err(ParseBoardSizeError::Malformed(s.to_string()))
+        }
+    }
+}
+
+// either one of the conventional sizes by name or something like 30x16x99
+impl FromStr for BoardSize {
+    type Err = ParseBoardSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(size) = s.parse::<ConventionalSize>() {
+            return Ok(size.size())
+        }
+
+        let numbers: Vec<usize> = s.split('x')
+                .map(|e| e.trim().parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| ParseBoardSizeError::Malformed(s.to_string()))?;
+
+        let [width, height, mines] = numbers[..] else {
+            return Err(ParseBoardSizeError::Malformed(s.to_string()))
+        };
+
+        BoardSize::new(width, height, mines)
+                .map_err(ParseBoardSizeError::Invalid)
+    }
 }
\ No newline at end of file
@@ -4,6 +4,8 @@ use std::fmt::{Debug, Display, Formatter};
 pub mod board;
 pub mod minsweeper;
 pub mod solver;
+#[cfg(feature = "server")]
+pub mod server;
 
 pub trait Minsweeper {
 
@@ -42,9 +44,10 @@ pub trait Minsweeper {
 
 }
 
+// moves can only be made on a game that's going, and before the first click only revealing does anything
 fn check_interact(minsweeper: &(impl Minsweeper + ?Sized), point: Point) -> Result<(), ()> {
     let state = minsweeper.gamestate();
-    if state.status == GameStatus::Playing
+    if state.status.is_ongoing()
             && (0..state.board.size().width().into()).contains(&point.0)
             && (0..state.board.size().height().into()).contains(&point.1) {
         Ok(())
@@ -74,7 +77,7 @@ pub trait GameStateTrait: Clone + Debug {
     fn remaining_mines(&self) -> usize;
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameState {
     pub status: GameStatus,
@@ -146,19 +149,59 @@ pub enum CellState {
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameStatus {
-    Playing, Won, Lost, Never
+    NotStarted, AwaitingFirstClick, Playing, Won, Lost
+}
+
+impl GameStatus {
+    // started and not over yet, whether or not the board's been made
+    pub const fn is_ongoing(self) -> bool {
+        matches!(self, GameStatus::AwaitingFirstClick | GameStatus::Playing)
+    }
+
+    pub const fn is_over(self) -> bool {
+        matches!(self, GameStatus::Won | GameStatus::Lost)
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::board::ConventionalSize;
-    use crate::minsweeper::MinsweeperGame;
-    use crate::solver::mia::MiaSolver;
+    use crate::board::{BoardSize, ConventionalSize};
+    use crate::minsweeper::coop::Coop;
+    use crate::minsweeper::race::Race;
+    use crate::minsweeper::{generate_game_from_seed, generate_solvable_game, generate_solvable_game_by_repair, MinsweeperGame, SetMinsweeperGame};
+    use crate::solver::analysis::analyse;
+    use crate::solver::mia::{Level, MiaLogic, MiaSolver};
+    use crate::solver::sat::SatSolver;
     use crate::solver::start::SafeStart;
-    use crate::solver::GameResult::Lost;
-    use crate::solver::Solver;
+    use crate::solver::target::Target;
+    use crate::solver::GameResult::{Lost, Resigned, Won};
+    use crate::solver::{Action, Actionable, Move, Operation, Solver};
+    use std::collections::HashSet;
+    use std::ops::Bound;
+
+    fn player_view(rows: &[&str], mines: usize) -> GameState {
+        let size = BoardSize::new(rows[0].chars().count(), rows.len(), mines).unwrap();
+        let mut board = Board::new(size, Cell::new(CellType::Unknown, CellState::Unknown));
+        let mut flags = 0;
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                board[(x, y)] = match cell {
+                    '▩' => continue,
+                    '!' => {
+                        flags += 1;
+                        Cell::new(CellType::Unknown, CellState::Flagged)
+                    }
+                    ' ' => Cell::new(CellType::EMPTY, CellState::Revealed),
+                    number => Cell::new(CellType::Safe(number.to_digit(10).unwrap() as u8), CellState::Revealed)
+                }
+            }
+        }
+
+        GameState::new(GameStatus::Playing, board, mines as isize - flags)
+    }
 
     #[test]
     fn it_works() {
@@ -180,6 +223,150 @@ mod tests {
                 .expect("shouldn't fail i don't think???");
     }
 
+    #[test]
+    fn games_should_say_where_they_are_in_their_lifecycle() {
+        let mut game = MinsweeperGame::new(ConventionalSize::Beginner.size(), || {}, || {});
+        assert_eq!(game.gamestate().status, GameStatus::NotStarted);
+        assert!(game.reveal((4, 4)).is_err());
+
+        assert_eq!(game.start_with_solver(MiaSolver::default()).status, GameStatus::AwaitingFirstClick);
+        // nothing to flag or chord around until there's a board
+        assert!(game.set_flagged((0, 0), true).is_err());
+        assert!(game.clear_around((4, 4)).is_err());
+
+        assert_eq!(game.left_click((4, 4)).unwrap().status, GameStatus::Playing);
+        assert!(game.gamestate().status.is_ongoing());
+
+        MiaSolver::default().solve_game(&mut game);
+        assert!(game.gamestate().status.is_over());
+        assert!(game.reveal((4, 4)).is_err());
+    }
+
+    #[test]
+    fn editor_should_keep_the_numbers_right_and_check_the_puzzle() {
+        use crate::minsweeper::editor::{Editor, EditorError};
+        use crate::minsweeper::position::PositionError;
+
+        let mut editor = Editor::new(5, 5).unwrap();
+        assert!(editor.layout().is_err());
+
+        // the two mines shut the corner off from the rest of the board
+        editor.set_mine((0, 1), true);
+        editor.toggle_mine((1, 0));
+        editor.toggle_mine((4, 3));
+        editor.toggle_mine((4, 3));
+        assert_eq!(editor.mines(), 2);
+        assert_eq!(editor.board()[(0, 0)].cell_type, CellType::Safe(2));
+        assert_eq!(editor.board()[(1, 1)].cell_type, CellType::Safe(2));
+        assert_eq!(editor.board()[(4, 2)].cell_type, CellType::EMPTY);
+
+        // nothing to go on yet
+        assert_eq!(editor.check(&MiaSolver::default()).unwrap().result, Resigned);
+
+        // opening the zero opens everything up to the numbers, but not the corner behind them
+        assert!(!editor.set_revealed((0, 1), true));
+        assert!(editor.set_revealed((2, 2), true));
+        assert_eq!(editor.board()[(1, 1)].cell_state, CellState::Revealed);
+        assert_eq!(editor.board()[(0, 0)].cell_state, CellState::Unknown);
+        assert_eq!(editor.check(&MiaSolver::default()).unwrap().result, Won);
+
+        let mut game = editor.game().unwrap();
+        assert_eq!(game.gamestate().board[(2, 2)].cell_state, CellState::Revealed);
+        assert_eq!(game.gamestate().remaining_mines, 2);
+        assert_eq!(game.reveal((0, 1)).unwrap().status, GameStatus::Lost);
+
+        // no click could leave a zero open with something hidden next to it
+        assert!(editor.set_revealed((3, 3), false));
+        assert!(matches!(editor.game(), Err(EditorError::Position(PositionError::UnopenedZero(_)))));
+    }
+
+    #[test]
+    fn positions_should_be_checked_and_playable() {
+        use crate::minsweeper::position::{Position, PositionError};
+
+        let layout = generate_game_from_seed(ConventionalSize::Beginner.size(), 7);
+        let size = layout.board.size();
+        let zero = size.points()
+                .find(|e| layout.board[*e].cell_type == CellType::EMPTY)
+                .unwrap();
+        let mine = size.points()
+                .find(|e| layout.board[*e].cell_type == CellType::Mine)
+                .unwrap();
+
+        // whatever clicking the zero opens up
+        let mut game = SetMinsweeperGame::new(layout.clone());
+        let opened = game.reveal(zero).unwrap().clone();
+        let revealed: Vec<_> = size.points()
+                .filter(|e| opened.board[*e].cell_state == CellState::Revealed)
+                .collect();
+
+        assert!(matches!(Position::new(&layout, [zero], []), Err(PositionError::UnopenedZero(_))));
+        assert!(matches!(Position::new(&layout, [mine], []), Err(PositionError::RevealedMine(_))));
+        assert!(matches!(Position::new(&layout, [], [(9, 0)]), Err(PositionError::OffBoard(_))));
+
+        let position = Position::new(&layout, revealed.iter().copied(), [mine]).unwrap();
+        let game = SetMinsweeperGame::from_position(position.clone());
+        assert_eq!(game.gamestate().remaining_mines, 9);
+        assert!(revealed.iter().all(|e| game.gamestate().board[*e] == opened.board[*e]));
+        assert_eq!(game.gamestate().board[mine].cell_state, CellState::Flagged);
+        assert_eq!(game.gamestate().board[mine].cell_type, CellType::Unknown);
+
+        // no first click, so no new board either
+        let mut game = MinsweeperGame::new(ConventionalSize::Expert.size(), || {}, || {});
+        game.start_with_solver(SafeStart);
+        assert_eq!(game.start_from_position(position).status, GameStatus::Playing);
+        assert_eq!(game.gamestate().board.size(), size);
+        game.set_flagged(mine, false).unwrap();
+        assert_eq!(game.reveal(mine).unwrap().status, GameStatus::Lost);
+
+        // the position was only the one game, the next one's the size the game was made with
+        assert_eq!(game.start().board.size(), ConventionalSize::Expert.size());
+        assert_eq!(game.reveal((0, 0)).unwrap().board.size(), ConventionalSize::Expert.size());
+
+        // a layout that says it has more mines than it does goes by the ones it has
+        let mut board = Board::empty(BoardSize::new(3, 1, 2).unwrap());
+        board[(0, 0)] = Cell::new(CellType::Mine, CellState::Unknown);
+        let position = Position::new(&GameState::new(GameStatus::Playing, board, 2), [], []).unwrap();
+        assert_eq!(position.state().board.size().mines().get(), 1);
+        assert_eq!(position.state().remaining_mines, 1);
+
+        // and one that's already won goes straight to winning
+        let won = std::cell::Cell::new(false);
+        let mut game: MinsweeperGame<SafeStart, _, _> = MinsweeperGame::new(ConventionalSize::Beginner.size(), || won.set(true), || {});
+        let solved = Position::new(position.state(), [(1, 0), (2, 0)], []).unwrap();
+        assert_eq!(game.start_from_position(solved).status, GameStatus::Won);
+        assert!(won.get());
+    }
+
+    #[test]
+    fn lucky_games_should_only_forgive_guesses() {
+        use crate::minsweeper::editor::Editor;
+        use crate::minsweeper::position::Position;
+
+        // both 1s see the same two cells, one of which is the mine
+        let mut editor = Editor::new(2, 2).unwrap();
+        editor.set_mine((0, 0), true);
+        let fifty_fifty = Position::new(&editor.layout().unwrap(), [(0, 1), (1, 1)], []).unwrap();
+
+        let mut game = MinsweeperGame::new(editor.layout().unwrap().board.size(), || {}, || {});
+        game.start_with_solver(SafeStart);
+        game.start_from_position(fifty_fifty.clone());
+        assert_eq!(game.reveal((0, 0)).unwrap().status, GameStatus::Lost);
+
+        game.set_lucky(true);
+        game.start_from_position(fifty_fifty);
+        assert_eq!(game.reveal((0, 0)).unwrap().status, GameStatus::Won);
+        assert_eq!(game.gamestate().board[(1, 0)].cell_type, CellType::Mine);
+        assert_eq!(game.stats().rescues, 1);
+
+        // the 1 in the middle also sees the two cells on the right, which the other 1 proves are safe
+        let mut editor = Editor::new(3, 2).unwrap();
+        editor.set_mine((0, 0), true);
+        game.start_from_position(Position::new(&editor.layout().unwrap(), [(0, 1), (1, 1)], []).unwrap());
+        assert_eq!(game.reveal((0, 0)).unwrap().status, GameStatus::Lost);
+        assert_eq!(game.stats().rescues, 0);
+    }
+
     #[test]
     fn mia_solver_should_never_die() {
         let mut game = MinsweeperGame::new(ConventionalSize::Expert.size(), || {}, || {});
@@ -198,6 +385,839 @@ mod tests {
         }
     }
 
+    #[test]
+    fn repaired_games_should_be_solvable() {
+        let size = BoardSize::new(16, 16, 64).unwrap();
+
+        for _ in 0..5 {
+            let state = generate_solvable_game_by_repair(size, &MiaSolver::default(), (8, 8));
+
+            let mut game = SetMinsweeperGame::new(state);
+            game.reveal((8, 8))
+                    .expect("first click shouldn't fail");
+
+            assert_eq!(MiaSolver::default().solve_game(&mut game), Won);
+        }
+    }
+
+    #[test]
+    fn brute_force_should_stay_within_the_mine_count() {
+        // a mine on the left plus two on the right would be one too many, so the left corners are safe
+        // the numbers get visited in whatever order a hash set gives, so try it a few times
+        let state = player_view(&[
+            "▩▩▩",
+            "1▩2",
+            "▩▩▩",
+        ], 2);
+
+        for _ in 0..32 {
+            let Move { actions, reason } = MiaSolver::default().solve(&state)
+                    .expect("brute force should find the safe corners");
+
+            assert_eq!(reason.unwrap().logic.to_string(), MiaLogic::BruteForce.to_string());
+            assert_eq!(actions, HashSet::from([Action::new((0, 0), Operation::Reveal), Action::new((0, 2), Operation::Reveal)]));
+        }
+    }
+
+    #[test]
+    fn bbbv_counts_openings_and_isolated_numbers() {
+        let mut board = Board::empty(BoardSize::new(5, 1, 1).unwrap());
+        board[(2, 0)] = Cell::new(CellType::Mine, CellState::Unknown);
+        board[(1, 0)] = Cell::new(CellType::Safe(1), CellState::Unknown);
+        board[(3, 0)] = Cell::new(CellType::Safe(1), CellState::Unknown);
+
+        assert_eq!(board.openings(), 2);
+        assert_eq!(board.bbbv(), 2);
+
+        let mut board = Board::empty(BoardSize::new(3, 1, 2).unwrap());
+        board[(0, 0)] = Cell::new(CellType::Mine, CellState::Unknown);
+        board[(1, 0)] = Cell::new(CellType::Safe(1), CellState::Unknown);
+        board[(2, 0)] = Cell::new(CellType::Mine, CellState::Unknown);
+
+        assert_eq!(board.openings(), 0);
+        assert_eq!(board.bbbv(), 1);
+    }
+
+    #[test]
+    fn targeted_games_should_meet_their_target() {
+        let target = Target::default()
+                .allow(MiaLogic::Chord | MiaLogic::FlagChord)
+                .bbbv(..=60);
+
+        let state = generate_solvable_game(ConventionalSize::Beginner.size(), &target, (4, 4));
+        assert!(state.board.bbbv() <= 60);
+
+        let mut game = SetMinsweeperGame::new(state.clone());
+        game.reveal((4, 4))
+                .expect("first click shouldn't fail");
+
+        let (result, counts) = MiaSolver::default().solve_game_counted(&mut game);
+        assert_eq!(result, Won);
+        assert!(counts.used().is_subset(MiaLogic::Chord | MiaLogic::FlagChord));
+
+        // a beginner board isn't enough for a solver that wants advanced logic
+        let mut game = SetMinsweeperGame::new(state);
+        game.reveal((4, 4))
+                .expect("first click shouldn't fail");
+        assert_eq!(Target::new(MiaSolver::only(Level::Advanced)).solve_game(&mut game), Resigned);
+    }
+
+    #[test]
+    fn empty_target_ranges_should_accept_nothing() {
+        let mut board = Board::empty(BoardSize::new(3, 1, 2).unwrap());
+        board[(0, 0)] = Cell::new(CellType::Mine, CellState::Unknown);
+        board[(1, 0)] = Cell::new(CellType::Safe(1), CellState::Unknown);
+        board[(2, 0)] = Cell::new(CellType::Mine, CellState::Unknown);
+        let state = GameState::new(GameStatus::Playing, board, 2);
+
+        let mut game = SetMinsweeperGame::new(state.clone());
+        game.reveal((1, 0))
+                .expect("the middle is safe");
+        assert_eq!(Target::default().openings(..0).solve_game(&mut game), Resigned);
+
+        let mut game = SetMinsweeperGame::new(state);
+        game.reveal((1, 0))
+                .expect("the middle is safe");
+        assert_eq!(Target::default().openings(..=0).bbbv((Bound::Excluded(0), Bound::Unbounded)).solve_game(&mut game), Won);
+    }
+
+    #[test]
+    fn traces_should_record_every_step() {
+        let state = generate_solvable_game(ConventionalSize::Intermediate.size(), &MiaSolver::default(), (0, 0));
+
+        let mut game = SetMinsweeperGame::new(state);
+        game.reveal((0, 0))
+                .expect("first click shouldn't fail");
+
+        let trace = MiaSolver::default().solve_game_traced(&mut game);
+
+        assert_eq!(trace.result, Won);
+        assert_eq!(trace.counts.total(), trace.steps.len());
+        assert_eq!(trace.steps.last().unwrap().after.status, GameStatus::Won);
+
+        for step in &trace.steps {
+            assert!(!step.actions.is_empty());
+            assert_eq!(step.before.status, GameStatus::Playing);
+        }
+    }
+
+    #[test]
+    fn hints_should_only_use_what_the_player_can_see() {
+        let mut game = MinsweeperGame::new(ConventionalSize::Beginner.size(), || {}, || {});
+        game.start_with_solver(MiaSolver::default());
+
+        assert!(game.hint().is_none());
+        game.reveal((4, 4))
+                .expect("first click shouldn't fail");
+
+        while game.gamestate().status == GameStatus::Playing {
+            let hint = game.hint()
+                    .expect("a solvable board should always have a hint");
+
+            assert!(!hint.explanation.is_empty());
+            for action in hint.actions {
+                let _ = game.action(action);
+            }
+        }
+
+        assert_eq!(game.gamestate().status, GameStatus::Won);
+        assert!(game.stats().hints > 0);
+    }
+
+    #[test]
+    fn hints_should_not_trust_wrong_flags() {
+        use crate::minsweeper::editor::Editor;
+        use crate::minsweeper::position::Position;
+
+        // the flag makes the 1 look done, but the mine is really on its other side
+        let mut editor = Editor::new(4, 1).unwrap();
+        editor.set_mine((2, 0), true);
+        let position = Position::new(&editor.layout().unwrap(), [(1, 0)], [(0, 0)]).unwrap();
+
+        let mut game: MinsweeperGame<SafeStart, _, _> = MinsweeperGame::new(position.state().board.size(), || {}, || {});
+        game.start_from_position(position);
+
+        let hint = game.hint()
+                .expect("the mine count still says where's safe");
+        assert_eq!(hint.actions, HashSet::from([Action::new((3, 0), Operation::Reveal)]));
+    }
+
+    #[test]
+    fn analysis_should_agree_with_the_real_layout() {
+        let state = generate_solvable_game(ConventionalSize::Intermediate.size(), &MiaSolver::default(), (8, 8));
+
+        let mut game = SetMinsweeperGame::new(state.clone());
+        game.reveal((8, 8))
+                .expect("first click shouldn't fail");
+
+        let trace = MiaSolver::default().solve_game_traced(&mut game);
+
+        for step in trace.steps.iter().step_by(5) {
+            let analysis = analyse(&step.before);
+
+            assert!(analysis.wrong_flags.is_empty());
+            assert!(analysis.broken_numbers.is_empty());
+            for point in &analysis.safe {
+                assert_ne!(state.board[*point].cell_type, CellType::Mine);
+            }
+            for point in &analysis.mines {
+                assert_eq!(state.board[*point].cell_type, CellType::Mine);
+            }
+
+            if let Some(safe) = analysis.safe.iter().next() {
+                let mut flagged = step.before.clone();
+                flagged.board[*safe].cell_state = CellState::Flagged;
+                flagged.remaining_mines -= 1;
+
+                assert!(analyse(&flagged).wrong_flags.contains(safe));
+            }
+        }
+    }
+
+    #[test]
+    fn sat_solver_should_never_die() {
+        let mut game = MinsweeperGame::new(ConventionalSize::Expert.size(), || {}, || {});
+
+        for _ in 0..20 {
+            game.start_with_solver(SafeStart);
+
+            game.reveal((0, 0))
+                    .expect("first click shouldn't fail");
+
+            if SatSolver.solve_game(&mut game) == Lost {
+                panic!("sat solver shouldn't lose\n{}", game.gamestate().board)
+            }
+        }
+    }
+
+    #[test]
+    fn sat_solver_should_solve_whatever_mia_solver_can() {
+        for _ in 0..5 {
+            let state = generate_solvable_game(ConventionalSize::Expert.size(), &MiaSolver::default(), (0, 0));
+
+            let mut game = SetMinsweeperGame::new(state);
+            game.reveal((0, 0))
+                    .expect("first click shouldn't fail");
+
+            assert_eq!(SatSolver.solve_game(&mut game), Won);
+        }
+    }
+
+    #[test]
+    fn elimination_should_combine_every_number_at_once() {
+        let state = player_view(&[
+            "▩▩▩▩▩▩▩▩▩",
+            "▩▩▩▩▩▩▩▩▩",
+            "▩▩▩1111▩▩",
+            "▩▩▩1  1▩▩",
+            "▩▩▩1  1▩▩",
+            "▩▩▩1111▩▩",
+            "▩▩▩▩▩▩▩▩▩",
+            "▩▩▩▩▩▩▩▩▩",
+            "▩▩▩▩▩▩▩▩▩",
+        ], 10);
+
+        assert!(MiaSolver::skill(Level::Intermediate).solve(&state).is_none());
+
+        let Move { actions, reason } = MiaSolver::skill(Level::Advanced).solve(&state)
+                .expect("elimination should find something here");
+
+        assert_eq!(reason.unwrap().logic.to_string(), MiaLogic::Elimination.to_string());
+
+        let analysis = analyse(&state);
+        for action in actions {
+            match action.operation {
+                Operation::Reveal => assert!(analysis.safe.contains(&action.point)),
+                _ => assert!(analysis.mines.contains(&action.point))
+            }
+        }
+    }
+
+    #[test]
+    fn mine_count_should_settle_the_cells_no_number_touches() {
+        // the 1 takes one of the two remaining mines, so the far cell has to be the other
+        let state = player_view(&["▩1▩▩"], 2);
+
+        assert!(MiaSolver::skill(Level::Advanced).solve(&state).is_none());
+
+        let Move { actions, reason } = MiaSolver::default().solve(&state)
+                .expect("the mine count should decide the far cell");
+
+        assert_eq!(reason.unwrap().logic.to_string(), MiaLogic::MineCount.to_string());
+        assert_eq!(actions, HashSet::from([Action::new((3, 0), Operation::Flag)]));
+    }
+
+    #[test]
+    fn uniqueness_should_point_out_the_cells_left_to_chance() {
+        use crate::solver::analysis::{uniqueness, Uniqueness};
+
+        assert_eq!(uniqueness(&player_view(&["1▩1"], 1)), Uniqueness::Unique);
+        assert_eq!(uniqueness(&player_view(&["1▩1"], 2)), Uniqueness::Inconsistent);
+        // both 1s see the same two cells, so the mine could be either
+        assert_eq!(uniqueness(&player_view(&["▩▩", "11"], 1)), Uniqueness::Ambiguous(HashSet::from([(0, 0), (1, 0)])));
+    }
+
+    #[test]
+    fn layouts_should_be_counted_and_sampled_evenly() {
+        use crate::solver::layouts::{count_layouts, Layouts};
+        use num_bigint::BigUint;
+
+        // the 1 has a mine on one side or the other, and the far cell takes whatever's left
+        let view = player_view(&["▩1▩▩"], 2);
+        assert_eq!(count_layouts(&view), Some(BigUint::from(2u8)));
+        assert_eq!(count_layouts(&player_view(&["1▩1"], 2)), Some(BigUint::ZERO));
+
+        let untouched = vec!["▩".repeat(30); 16];
+        let untouched: Vec<_> = untouched.iter().map(String::as_str).collect();
+        assert!(count_layouts(&player_view(&untouched, 99)).unwrap() > BigUint::from(u128::MAX));
+
+        let view = player_view(&["▩▩▩", "1▩▩", "▩▩▩"], 3);
+        let layouts = Layouts::new(&view).unwrap();
+        let mut rng = fastrand::Rng::with_seed(0);
+        let mut left = 0;
+
+        for _ in 0..1000 {
+            let layout = layouts.sample(&mut rng).unwrap();
+            // they all look exactly like the view to a player
+            assert_eq!(SetMinsweeperGame::new(layout.clone()).gamestate().board, view.board);
+            assert_eq!(layout.board.iter().filter(|e| e.cell_type == CellType::Mine).count(), 3);
+
+            if layout.board[(0, 0)].cell_type == CellType::Mine {
+                left += 1;
+            }
+        }
+
+        // out of every layout, (0, 0) is a mine in exactly a fifth of them
+        assert!((150..250).contains(&left), "{left}");
+    }
+
+    #[test]
+    fn races_should_rank_players_by_when_they_finish() {
+        let mut race = Race::from_seed(ConventionalSize::Beginner.size(), 42, (4, 4)).unwrap();
+        assert_eq!(race.layout().board.to_string(), Race::<()>::from_seed(ConventionalSize::Beginner.size(), 42, (4, 4)).unwrap().layout().board.to_string());
+        assert!(Race::<()>::from_seed(ConventionalSize::Beginner.size(), 42, (9, 4)).is_none());
+        assert!(Race::<()>::from_seed(BoardSize::new(3, 3, 8).unwrap(), 42, (1, 1)).is_none());
+        assert!(!race.is_over());
+        assert_eq!(race.layout().board[(4, 4)], Cell::new(CellType::EMPTY, CellState::Revealed));
+
+        let layout = race.layout().board.clone();
+        let size = layout.size();
+        let mine = size.points().find(|e| layout[*e].cell_type == CellType::Mine).unwrap();
+
+        race.join("alice");
+        race.join("bob");
+        race.join("carol");
+
+        assert!(race.left_click(&"alice", mine).is_none(), "nobody should move before the start");
+        race.start();
+
+        race.left_click(&"bob", mine);
+
+        for point in size.points().filter(|e| layout[*e].cell_type != CellType::Mine) {
+            if race.gamestate(&"alice").unwrap().board[point].cell_state == CellState::Unknown {
+                race.action(&"alice", Action::new(point, Operation::Reveal));
+            }
+        }
+
+        assert_eq!(race.progress(&"alice"), Some(1.0));
+        // carol hasn't moved, so only the opening everyone started with is revealed
+        assert!(race.progress(&"carol").is_some_and(|e| e > 0.0 && e < 1.0));
+        assert_eq!(race.gamestate(&"carol").unwrap().board[(4, 4)].cell_state, CellState::Revealed);
+        assert_eq!(race.finishes().iter().map(|e| (e.player, e.status)).collect::<Vec<_>>(),
+            vec![("bob", GameStatus::Lost), ("alice", GameStatus::Won)]);
+        assert_eq!(race.winner().map(|e| e.player), Some("alice"));
+        assert!(!race.is_over());
+    }
+
+    #[test]
+    fn coop_should_remember_who_did_what() {
+        let layout = generate_game_from_seed(ConventionalSize::Beginner.size(), 7);
+        let size = layout.board.size();
+        let mines: Vec<_> = size.points()
+                .filter(|e| layout.board[*e].cell_type == CellType::Mine)
+                .collect();
+        let safe = size.points()
+                .find(|e| layout.board[*e].cell_type != CellType::Mine)
+                .unwrap();
+
+        let mut coop = Coop::with_private_flags(SetMinsweeperGame::new(layout));
+
+        coop.reveal(&"alice", safe).unwrap();
+        coop.toggle_flag(&"bob", mines[0]).unwrap();
+
+        assert_eq!(coop.attribution().revealed_by.get(&safe), Some(&"alice"));
+        assert_eq!(coop.view(&"bob").board[mines[0]].cell_state, CellState::Flagged);
+        assert_eq!(coop.view(&"alice").board[mines[0]].cell_state, CellState::Unknown);
+        assert_eq!(coop.view(&"bob").remaining_mines, coop.view(&"alice").remaining_mines - 1);
+
+        coop.reveal(&"carol", mines[1]).unwrap();
+
+        assert_eq!(coop.attribution().loser, Some("carol"));
+        assert_eq!(coop.attribution().revealed_by.get(&mines[1]), Some(&"carol"));
+
+        // starting over goes back to the same layout with nobody credited for anything
+        let state = coop.start().clone();
+        assert_eq!(state.status, GameStatus::Playing);
+        assert!(state.board.iter().all(|e| e.cell_state == CellState::Unknown));
+        assert!(coop.attribution().revealed_by.is_empty());
+        coop.reveal(&"alice", safe).unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn subscribers_should_be_able_to_follow_along() {
+        use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+            let game = AsyncMinsweeperGame::new(ConventionalSize::Expert.size(), (|| {}) as fn(), (|| {}) as fn());
+            let mut changes = game.subscribe();
+
+            let mut view = game.start_with_solver(SafeStart).await;
+            assert_eq!(changes.try_recv().unwrap().status, GameStatus::AwaitingFirstClick);
+
+            game.reveal((15, 8)).await.unwrap();
+            let change = changes.try_recv().unwrap();
+            assert!(change.points().all(|e| view.board[e].cell_state == CellState::Unknown));
+            change.apply(&mut view);
+
+            let unknown = view.board.size().points()
+                    .find(|e| view.board[*e].cell_state == CellState::Unknown)
+                    .unwrap();
+            game.set_flagged(unknown, true).await.unwrap();
+            let change = changes.try_recv().unwrap();
+            assert_eq!(change.points().collect::<Vec<_>>(), vec![unknown]);
+            change.apply(&mut view);
+
+            let state = game.gamestate().await;
+            assert_eq!(view.board.to_string(), state.board.to_string());
+            assert_eq!(view.remaining_mines, state.remaining_mines);
+
+            // moves that don't go through don't tell anyone anything
+            let _ = game.reveal(unknown).await;
+            assert!(changes.try_recv().is_err());
+        });
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn concurrent_first_clicks_should_generate_one_board() {
+        use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+        use crate::solver::GameResult;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // takes any board, so every generation asks it exactly once, and takes its time so other clicks pile up meanwhile
+        #[derive(Clone)]
+        struct Counting(Arc<AtomicUsize>);
+
+        impl Solver for Counting {
+            fn solve(&self, _: &GameState) -> Option<Move> {
+                None
+            }
+
+            fn solve_game(&self, _: &mut dyn Minsweeper) -> GameResult {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                Won
+            }
+        }
+
+        let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(4).build().unwrap();
+
+        for _ in 0..50 {
+            let generations = Arc::new(AtomicUsize::new(0));
+            let game = Arc::new(AsyncMinsweeperGame::new(ConventionalSize::Expert.size(), (|| {}) as fn(), (|| {}) as fn()));
+
+            let (clicked, state) = runtime.block_on(async {
+                game.start_with_solver(Counting(generations.clone())).await;
+
+                let clicks: Vec<_> = (0..16)
+                        .map(|x| {
+                            let game = game.clone();
+                            tokio::spawn(async move { (x, game.left_click((x, x)).await.is_ok()) })
+                        })
+                        .collect();
+
+                let mut clicked = vec![];
+                for click in clicks {
+                    let (x, success) = click.await.unwrap();
+                    if success {
+                        clicked.push((x, x));
+                    }
+                }
+                (clicked, game.gamestate().await)
+            });
+
+            assert_eq!(generations.load(Ordering::SeqCst), 1);
+            // nobody got to click on the board before it was there
+            assert!(clicked.iter().all(|e| state.board[*e].cell_state == CellState::Revealed));
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn coop_should_only_credit_players_with_their_own_moves() {
+        use crate::minsweeper::coop::nonblocking::AsyncCoop;
+        use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+        use std::sync::Arc;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(4).build().unwrap();
+
+        for _ in 0..20 {
+            let coop = Arc::new(AsyncCoop::new(AsyncMinsweeperGame::new(ConventionalSize::Expert.size(), (|| {}) as fn(), (|| {}) as fn())));
+
+            let (bobs, attribution) = runtime.block_on(async {
+                coop.start_with_solver(SafeStart).await;
+                let state = coop.left_click(&"alice", (0, 0)).await.unwrap();
+
+                let unknown: Vec<_> = state.board.size().points()
+                        .filter(|e| state.board[*e].cell_state == CellState::Unknown)
+                        .collect();
+                let (bobs, others) = unknown.split_at(unknown.len() / 2);
+                let (bobs, others) = (bobs.to_vec(), others.to_vec());
+
+                // flags placed straight on the game belong to nobody, however they line up with bob's
+                let direct = {
+                    let coop = coop.clone();
+                    tokio::spawn(async move {
+                        for point in others {
+                            let _ = coop.game().set_flagged(point, true).await;
+                        }
+                    })
+                };
+                let bob = {
+                    let (coop, bobs) = (coop.clone(), bobs.clone());
+                    tokio::spawn(async move {
+                        for point in bobs {
+                            let _ = coop.set_flagged(&"bob", point, true).await;
+                        }
+                    })
+                };
+                direct.await.unwrap();
+                bob.await.unwrap();
+
+                (bobs, coop.attribution().await)
+            });
+
+            assert_eq!(attribution.flagged_by.keys().copied().collect::<HashSet<_>>(), bobs.into_iter().collect());
+            assert!(attribution.flagged_by.values().all(|e| *e == "bob"));
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn concurrent_moves_should_replay_in_order() {
+        use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+        use std::sync::Arc;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(4).build().unwrap();
+
+        for seed in 0..20 {
+            let game = Arc::new(AsyncMinsweeperGame::new(ConventionalSize::Intermediate.size(), (|| {}) as fn(), (|| {}) as fn()));
+
+            let (view, state) = runtime.block_on(async {
+                let mut changes = game.subscribe();
+                let mut view = game.start_with_solver(SafeStart).await;
+                changes.recv().await.unwrap().apply(&mut view);
+
+                let players: Vec<_> = (0..8)
+                        .map(|player| {
+                            let game = game.clone();
+                            tokio::spawn(async move {
+                                let mut rng = fastrand::Rng::with_seed(seed * 8 + player);
+                                for _ in 0..20 {
+                                    let point = (rng.usize(..16), rng.usize(..16));
+                                    let _ = match rng.u8(..3) {
+                                        0 => game.right_click(point).await,
+                                        _ => game.left_click(point).await
+                                    };
+                                }
+                            })
+                        })
+                        .collect();
+                for player in players {
+                    player.await.unwrap();
+                }
+
+                // every change went out while its move still held the lock, so they arrive in the order the moves happened
+                let state = game.gamestate().await;
+                while let Ok(change) = changes.try_recv() {
+                    change.apply(&mut view);
+                }
+                (view, state)
+            });
+
+            // the layout only shows up once the game is over, and changes only carry what the player can see
+            if state.status == GameStatus::Playing {
+                assert_eq!(view.board.to_string(), state.board.to_string());
+            }
+            assert_eq!(view.status, state.status);
+            assert_eq!(view.remaining_mines, state.remaining_mines);
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn generation_should_report_progress_and_stop_when_asked() {
+        use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+        use crate::minsweeper::Generation;
+        use std::sync::Arc;
+
+        // never wins, so generating for it goes on until someone cancels it
+        struct Hopeless;
+
+        impl Solver for Hopeless {
+            fn solve(&self, _: &GameState) -> Option<Move> {
+                None
+            }
+        }
+
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+            let game = Arc::new(AsyncMinsweeperGame::new(ConventionalSize::Expert.size(), (|| {}) as fn(), (|| {}) as fn()));
+            let mut generation = game.generation();
+
+            let hopeless: Arc<dyn Solver + Send + Sync> = Arc::new(Hopeless);
+            game.start_with_solver(hopeless.clone()).await;
+
+            let reveal = tokio::spawn({
+                let game = game.clone();
+                async move { game.reveal((15, 8)).await }
+            });
+            generation.wait_for(|e| matches!(e, Generation::Running { attempts } if *attempts > 10)).await.unwrap();
+
+            // restarting doesn't wait for a board that's never coming
+            let state = game.start_with_solver(hopeless).await;
+            assert!(reveal.await.unwrap().is_err());
+            assert!(matches!(*generation.borrow(), Generation::Cancelled { .. }));
+            assert!(state.board.iter().all(|e| e.cell_state == CellState::Unknown));
+
+            // and nor does giving up on the click
+            let reveal = tokio::spawn({
+                let game = game.clone();
+                async move { game.reveal((15, 8)).await }
+            });
+            generation.wait_for(|e| matches!(e, Generation::Running { .. })).await.unwrap();
+            reveal.abort();
+            generation.wait_for(|e| matches!(e, Generation::Cancelled { .. })).await.unwrap();
+
+            game.start_with_solver(Arc::new(SafeStart)).await;
+            game.reveal((15, 8)).await.unwrap();
+            assert!(matches!(*generation.borrow(), Generation::Done { attempts } if attempts > 0));
+        });
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn restarts_should_stop_generation_however_soon_they_come() {
+        use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        struct Hopeless;
+
+        impl Solver for Hopeless {
+            fn solve(&self, _: &GameState) -> Option<Move> {
+                None
+            }
+        }
+
+        // a restart that gets stuck behind generation never comes back, so this watches from another thread
+        let (done, finished) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            tokio::runtime::Builder::new_multi_thread().worker_threads(4).build().unwrap().block_on(async {
+                let hopeless: Arc<dyn Solver + Send + Sync> = Arc::new(Hopeless);
+
+                for _ in 0..200 {
+                    let game = Arc::new(AsyncMinsweeperGame::new(ConventionalSize::Expert.size(), (|| {}) as fn(), (|| {}) as fn()));
+                    game.start_with_solver(hopeless.clone()).await;
+
+                    let reveal = tokio::spawn({
+                        let game = game.clone();
+                        async move { game.reveal((15, 8)).await }
+                    });
+                    game.start_with_solver(hopeless.clone()).await;
+
+                    // the reveal might not have got going until after that restart, so keep at it until it gives up
+                    while !reveal.is_finished() {
+                        game.start_with_solver(hopeless.clone()).await;
+                        tokio::task::yield_now().await;
+                    }
+                    assert!(reveal.await.unwrap().is_err());
+                }
+            });
+            let _ = done.send(());
+        });
+
+        finished.recv_timeout(Duration::from_secs(60))
+                .expect("restarting shouldn't wait for a board that's never coming");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn sync_solvers_should_think_off_the_runtime() {
+        use crate::solver::nonblocking::AsyncSolver;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Clone)]
+        struct Slow;
+
+        impl Solver for Slow {
+            fn solve(&self, _: &GameState) -> Option<Move> {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                None
+            }
+        }
+
+        // with only the one thread, the ticker only gets anywhere if the solver isn't sitting on it
+        let ticks = Arc::new(AtomicUsize::new(0));
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+            let ticker = tokio::spawn({
+                let ticks = ticks.clone();
+                async move {
+                    loop {
+                        ticks.fetch_add(1, Ordering::Relaxed);
+                        tokio::task::yield_now().await;
+                    }
+                }
+            });
+
+            assert!(Slow.solve_async(&player_view(&["▩1▩▩"], 2)).await.is_none());
+            ticker.abort();
+        });
+
+        assert!(ticks.load(Ordering::Relaxed) > 1000, "{}", ticks.load(Ordering::Relaxed));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn bots_should_play_through_the_shared_game() {
+        use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+        use crate::solver::nonblocking::AsyncSolver;
+        use std::sync::Arc;
+
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+            let game = Arc::new(AsyncMinsweeperGame::new(ConventionalSize::Intermediate.size(), (|| {}) as fn(), (|| {}) as fn()));
+            game.start_with_solver(MiaSolver::default()).await;
+            game.reveal((8, 8)).await.unwrap();
+
+            // a bot running off on its own task, the way it would next to people's connections
+            let bot = tokio::spawn({
+                let game = game.clone();
+                async move {
+                    let mut moves = 0;
+                    let result = MiaSolver::default().solve_game_async(&*game, |_, _| moves += 1).await;
+                    (result, moves)
+                }
+            });
+
+            let (result, moves) = bot.await.unwrap();
+            assert_eq!(result, Won);
+            assert!(moves > 0);
+            assert_eq!(game.gamestate().await.status, GameStatus::Won);
+        });
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn game_overs_should_be_handled_outside_the_lock() {
+        use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+        use crate::solver::nonblocking::AsyncSolver;
+        use std::sync::Arc;
+
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+            let game = Arc::new(AsyncMinsweeperGame::new(ConventionalSize::Beginner.size(), (|| {}) as fn(), (|| {}) as fn()));
+            let mut game_overs = game.game_overs();
+
+            // awaits the game itself, which would never finish if it ran while the move still held the lock
+            let handler = tokio::spawn({
+                let game = game.clone();
+                async move {
+                    let game_over = game_overs.recv().await.unwrap();
+                    (game_over, game.gamestate().await)
+                }
+            });
+
+            game.start_with_solver(MiaSolver::default()).await;
+            game.reveal((4, 4)).await.unwrap();
+            assert_eq!(MiaSolver::default().solve_game_async(&*game, |_, _| {}).await, Won);
+
+            let (game_over, state) = handler.await.unwrap();
+            assert_eq!(game_over.state, state);
+            assert_eq!(game_over.state.status, GameStatus::Won);
+        });
+    }
+
+    #[cfg(feature = "server")]
+    #[tokio::test]
+    async fn server_should_share_rooms_without_showing_the_layout() {
+        use crate::server::{ClientMessage, Server, ServerMessage};
+        use futures_util::{SinkExt, Stream, StreamExt};
+        use std::sync::Arc;
+        use tokio_tungstenite::tungstenite::{Error, Message};
+
+        async fn receive(socket: &mut (impl Stream<Item = Result<Message, Error>> + Unpin)) -> ServerMessage {
+            loop {
+                if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                    return serde_json::from_str(&text).unwrap()
+                }
+            }
+        }
+        fn send(message: ClientMessage) -> Message {
+            Message::Text(serde_json::to_string(&message).unwrap().into())
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = Arc::new(Server::new(ConventionalSize::Beginner.size()).with_solver(SafeStart));
+        tokio::spawn(server.serve(listener));
+
+        let (mut alice, _) = tokio_tungstenite::connect_async(format!("ws://{address}/room")).await.unwrap();
+        let (mut bob, _) = tokio_tungstenite::connect_async(format!("ws://{address}/room")).await.unwrap();
+
+        for socket in [&mut alice, &mut bob] {
+            let ServerMessage::State { state } = receive(socket).await else { panic!("should start with the whole board") };
+            assert!(state.board.iter().all(|e| *e == Cell::new(CellType::Unknown, CellState::Unknown)));
+        }
+
+        alice.send(send(ClientMessage::Reveal { point: (4, 4) })).await.unwrap();
+
+        let ServerMessage::Diff { status, cells, .. } = receive(&mut alice).await else { panic!("should get a diff") };
+        let ServerMessage::Diff { cells: bobs_cells, .. } = receive(&mut bob).await else { panic!("should get a diff") };
+
+        assert_eq!(cells, bobs_cells);
+        assert!(!cells.is_empty());
+        if status == GameStatus::Playing {
+            assert!(cells.iter().all(|(_, cell)| cell.cell_state == CellState::Revealed && cell.cell_type != CellType::Mine));
+        }
+
+        // only whoever made a bad move hears about it
+        bob.send(send(ClientMessage::Flag { point: (4, 4) })).await.unwrap();
+        assert!(matches!(receive(&mut bob).await, ServerMessage::Rejected { .. }));
+
+        let (mut carol, _) = tokio_tungstenite::connect_async(format!("ws://{address}/elsewhere")).await.unwrap();
+        let ServerMessage::State { state } = receive(&mut carol).await else { panic!("should start with the whole board") };
+        assert!(state.board.iter().all(|e| e.cell_state == CellState::Unknown));
+
+        // once everyone's left the room goes with them, so coming back starts over
+        alice.close(None).await.unwrap();
+        bob.close(None).await.unwrap();
+
+        let fresh = async {
+            loop {
+                let (mut dave, _) = tokio_tungstenite::connect_async(format!("ws://{address}/room")).await.unwrap();
+                let ServerMessage::State { state } = receive(&mut dave).await else { panic!("should start with the whole board") };
+                if state.board.iter().all(|e| e.cell_state == CellState::Unknown) {
+                    break
+                }
+                drop(dave);
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), fresh).await
+                .expect("the room should've been dropped once it was empty");
+    }
+
     #[test]
     fn mewo() {
         println!("{:#x}", 16742399)
@@ -1,9 +1,17 @@
 use crate::board::{Board, BoardSize, Point};
-use crate::solver::{GameResult, Solver};
+use crate::solver::analysis::analyse;
+use crate::solver::layouts::sample_layout;
+use crate::solver::mia::MiaSolver;
+use crate::solver::{Action, GameResult, Move, Operation, Solver};
 use crate::{check_interact, Cell, CellState, CellType, GameState, GameStatus, Minsweeper};
 use std::collections::HashSet;
 use std::ops::{Deref, DerefMut};
 
+pub mod coop;
+pub mod editor;
+pub mod position;
+pub mod race;
+
 trait InternalMinsweeper {
 
     fn start(&mut self) -> &GameState;
@@ -193,11 +201,33 @@ impl<T: InternalMinsweeper + ?Sized> Minsweeper for T {
 
 
 pub fn generate_game(board_size: BoardSize) -> GameState {
+    generate_game_with(board_size, &mut fastrand::Rng::new(), None)
+}
+
+// the same seed always gives the same layout
+pub fn generate_game_from_seed(board_size: BoardSize, seed: u64) -> GameState {
+    generate_game_with(board_size, &mut fastrand::Rng::with_seed(seed), None)
+}
+
+// same again, but keeping `point` and everything around it clear of mines when there's room for them elsewhere
+pub fn generate_game_from_seed_around(board_size: BoardSize, seed: u64, point: Point) -> GameState {
+    generate_game_with(board_size, &mut fastrand::Rng::with_seed(seed), Some(point))
+}
+
+fn generate_game_with(board_size: BoardSize, rng: &mut fastrand::Rng, opening: Option<Point>) -> GameState {
     let mut board = Board::empty(board_size);
 
     let mine = Cell::new(CellType::Mine, CellState::Unknown);
     let mut points: Vec<Point> = board_size.points().collect();
-    fastrand::shuffle(&mut points);
+    rng.shuffle(&mut points);
+    // the opening goes to the back so mines only land there once everywhere else is full
+    if let Some(opening) = opening {
+        points.sort_by_key(|e| match (e.0.abs_diff(opening.0), e.1.abs_diff(opening.1)) {
+            (0, 0) => 2,
+            (0..=1, 0..=1) => 1,
+            _ => 0
+        });
+    }
     for point in &points[..board_size.mines().get()] {
         board[*point] = mine;
     }
@@ -207,20 +237,20 @@ pub fn generate_game(board_size: BoardSize) -> GameState {
     GameState::new(GameStatus::Playing, board, usize::from(board_size.mines()).try_into().unwrap())
 }
 
-fn generate_nmbers(board: &mut Board) {
-    let empty_unknown = Cell::new(CellType::EMPTY, CellState::Unknown);
+pub(crate) fn generate_nmbers(board: &mut Board) {
+    // cell states are left alone so this can also renumber a board that's mid-game
     for point in board.size().points() {
         let cell = &mut board[point];
 
         if matches!(cell.cell_type, CellType::Safe(_)) {
-            *cell = empty_unknown;
+            cell.cell_type = CellType::EMPTY;
         }
     }
     for point in board.size().points() {
         if board[point].cell_type == CellType::Mine {
             for point in board.size().neighbours(point) {
                 if let CellType::Safe(number) = board[point].cell_type {
-                    board[point] = Cell::new(CellType::Safe(number + 1), CellState::Unknown);
+                    board[point].cell_type = CellType::Safe(number + 1);
                 }
             }
         }
@@ -237,8 +267,9 @@ pub struct MinsweeperGame<
     player_game_state: GameState,
     on_win: OnWin,
     on_lose: OnLose,
-    first: bool,
-    solver: Option<S>
+    solver: Option<S>,
+    lucky: bool,
+    stats: GameStats
 }
 
 impl<S: Solver, OnWin: Fn(), OnLose: Fn()> MinsweeperGame<S, OnWin, OnLose> {
@@ -246,21 +277,23 @@ impl<S: Solver, OnWin: Fn(), OnLose: Fn()> MinsweeperGame<S, OnWin, OnLose> {
     pub fn new(board_size: BoardSize, on_win: OnWin, on_lose: OnLose) -> Self {
         Self {
             board_size,
-            game_state: GameState::new(GameStatus::Never, Board::empty(board_size), 0),
-            player_game_state: GameState::new(GameStatus::Never, Board::empty(board_size), 0),
+            game_state: GameState::new(GameStatus::NotStarted, Board::empty(board_size), 0),
+            player_game_state: GameState::new(GameStatus::NotStarted, Board::empty(board_size), 0),
             on_win,
             on_lose,
-            first: true,
-            solver: None
+            solver: None,
+            lucky: false,
+            stats: GameStats::default()
         }
     }
 
     fn internal_start(&mut self, solver: Option<S>) -> &GameState {
-        *self.gamestate_mut() = GameState::new(GameStatus::Playing, Board::empty(self.board_size),
+        // the board only gets made on the first click, so it can be made around wherever that is
+        *self.gamestate_mut() = GameState::new(GameStatus::AwaitingFirstClick, Board::empty(self.board_size),
                                          usize::from(self.board_size.mines()).try_into().unwrap());
 
-        self.first = true;
         self.solver = solver;
+        self.stats = GameStats::default();
 
         self.player_gamestate()
     }
@@ -268,6 +301,117 @@ impl<S: Solver, OnWin: Fn(), OnLose: Fn()> MinsweeperGame<S, OnWin, OnLose> {
     pub fn start_with_solver(&mut self, solver: S) -> &GameState {
         self.internal_start(solver.into())
     }
+
+    pub fn stats(&self) -> GameStats {
+        self.stats
+    }
+
+    // when a click lands on a mine but the player had nothing to go on but a guess,
+    // the mines get quietly moved to some other layout that fits everything they've seen and leaves that cell safe
+    // clicking a mine when there was a safe cell to be found still loses
+    pub fn set_lucky(&mut self, lucky: bool) {
+        self.lucky = lucky;
+    }
+
+    pub fn is_lucky(&self) -> bool {
+        self.lucky
+    }
+
+    fn rescue(&mut self, point: Point) {
+        let analysis = analyse(self.player_gamestate());
+        if !analysis.complete || !analysis.safe.is_empty() || !analysis.wrong_flags.is_empty() {
+            return
+        }
+
+        // revealed with no number yet, which is how layouts are told the cell has to be safe
+        let mut view = self.player_gamestate().clone();
+        view.board[point] = Cell::new(CellType::Unknown, CellState::Revealed);
+
+        // there's no such layout if the cell could only ever have been a mine
+        let Some(layout) = sample_layout(&view, &mut fastrand::Rng::new()) else { return };
+
+        let mut state = self.gamestate_mut();
+        for point in layout.board.size().points() {
+            state.board[point].cell_type = layout.board[point].cell_type;
+        }
+        drop(state);
+
+        self.stats.rescues += 1;
+    }
+
+    // only ever looks at what the player can see, so a hint can't give away anything they couldn't have worked out
+    pub fn hint(&mut self) -> Option<Hint> {
+        let hint = Hint::for_state(self.player_gamestate())?;
+
+        self.stats.hints += 1;
+
+        Some(hint)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameStats {
+    pub hints: usize,
+    pub rescues: usize
+}
+
+#[derive(Clone, Debug)]
+pub struct Hint {
+    pub actions: HashSet<Action>,
+    pub related: HashSet<Point>,
+    pub explanation: String
+}
+
+impl Hint {
+    // the player's flags could be wrong, so only the ones the numbers prove are kept for the solver to lean on
+    // and anything it comes up with still has to be backed up by the analysis
+    fn for_state(state: &GameState) -> Option<Self> {
+        let size = state.board.size();
+
+        let mut view = state.clone();
+        for point in size.points() {
+            if view.board[point].cell_state == CellState::Flagged {
+                view.board[point].cell_state = CellState::Unknown;
+                view.remaining_mines += 1;
+            }
+        }
+
+        let analysis = analyse(&view);
+        let is_flagged = |point: Point| state.board[point].cell_state == CellState::Flagged;
+        let is_proven = |point: Point| analysis.mines.contains(&point);
+
+        for point in size.points() {
+            if is_flagged(point) && is_proven(point) {
+                view.board[point].cell_state = CellState::Flagged;
+                view.remaining_mines -= 1;
+            }
+        }
+
+        let Move { actions, reason } = MiaSolver::default().solve(&view)?;
+        let reason = reason?;
+
+        let actions: HashSet<_> = actions.into_iter()
+                .filter(|action| match action.operation {
+                    Operation::Reveal => analysis.safe.contains(&action.point) && !is_flagged(action.point),
+                    Operation::Flag => is_proven(action.point) && !is_flagged(action.point),
+                    // a chord goes by the player's own flags, so every one of them around it has to be right
+                    Operation::Chord => size.neighbours(action.point)
+                            .filter(|e| is_flagged(*e))
+                            .all(is_proven)
+                })
+                .collect();
+
+        if actions.is_empty() {
+            return None
+        }
+
+        Some(Self {
+            actions,
+            related: reason.related,
+            explanation: reason.logic.to_string()
+        })
+    }
 }
 
 impl<S: Solver, OnWin: Fn(), OnLose: Fn()> InternalMinsweeper for MinsweeperGame<S, OnWin, OnLose> {
@@ -284,7 +428,7 @@ impl<S: Solver, OnWin: Fn(), OnLose: Fn()> InternalMinsweeper for MinsweeperGame
     }
 
     fn player_gamestate(&self) -> &GameState {
-        if self.game_state.status == GameStatus::Playing {
+        if self.game_state.status.is_ongoing() {
             &self.player_game_state
         } else {
             &self.game_state
@@ -303,9 +447,7 @@ impl<S: Solver, OnWin: Fn(), OnLose: Fn()> InternalMinsweeper for MinsweeperGame
             return Err(self.player_gamestate())
         }
 
-        if self.first {
-            self.first = false;
-
+        if self.game_state.status == GameStatus::AwaitingFirstClick {
             if let Some(solver) = &self.solver {
                 *self.gamestate_mut() = generate_solvable_game(self.board_size, solver, point);
             } else {
@@ -313,6 +455,11 @@ impl<S: Solver, OnWin: Fn(), OnLose: Fn()> InternalMinsweeper for MinsweeperGame
             }
         }
 
+        // chords aren't guesses, so only plain reveals ever get rescued
+        if self.lucky && self.game_state.board[point] == Cell::new(CellType::Mine, CellState::Unknown) {
+            self.rescue(point);
+        }
+
 
         let success = self.internal_reveal(point);
 
@@ -336,7 +483,7 @@ impl<S: Solver, OnWin: Fn(), OnLose: Fn()> InternalMinsweeper for MinsweeperGame
     }
 
     fn set_flagged(&mut self, point: Point, flagged: bool) -> Result<&GameState, &GameState> {
-        if check_interact(self, point).is_err() || self.first {
+        if check_interact(self, point).is_err() || self.game_state.status == GameStatus::AwaitingFirstClick {
             return Err(self.player_gamestate())
         }
 
@@ -364,38 +511,224 @@ impl<S: Solver, OnWin: Fn(), OnLose: Fn()> InternalMinsweeper for MinsweeperGame
 #[cfg(feature = "async")]
 pub mod nonblocking {
     use crate::board::{BoardSize, Point};
-    use crate::minsweeper::{generate_game, generate_solvable_game_async, InternalMinsweeper, MinsweeperGame};
+    use crate::minsweeper::{generate_game, GameStats, Hint, InternalMinsweeper, MinsweeperGame};
     use crate::solver::Solver;
-    use crate::{check_interact, Cell, CellState, CellType, GameState, Minsweeper};
-    use tokio::sync::{Mutex, RwLock};
+    use crate::{check_interact, Cell, CellState, CellType, GameState, GameStatus, Minsweeper};
+    use std::sync::Mutex;
+    use tokio::sync::{broadcast, mpsc, RwLock, RwLockWriteGuard};
+    #[cfg(not(feature = "tokio"))]
+    use crate::minsweeper::try_generate_solvable_game;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    #[cfg(feature = "tokio")]
+    use {
+        crate::minsweeper::{generate_solvable_game_off_thread, Generation},
+        tokio::sync::watch
+    };
+
+    // sent to every subscriber whenever a move or a restart changes what the player can see
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Change {
+        pub cells: Vec<(Point, Cell)>,
+        pub status: GameStatus,
+        pub remaining_mines: isize
+    }
+
+    impl Change {
+        pub fn points(&self) -> impl Iterator<Item = Point> {
+            self.cells.iter()
+                    .map(|(point, _)| *point)
+        }
 
+        // applies the change to an earlier copy of the player's view
+        pub fn apply(&self, state: &mut GameState) {
+            for (point, cell) in &self.cells {
+                state.board[*point] = *cell;
+            }
+            state.status = self.status;
+            state.remaining_mines = self.remaining_mines;
+        }
+
+        fn between(before: &GameState, after: &GameState) -> Option<Self> {
+            let cells: Vec<_> = after.board.size().points()
+                    .filter(|e| before.board[*e] != after.board[*e])
+                    .map(|e| (e, after.board[e]))
+                    .collect();
+
+            if cells.is_empty() && before.status == after.status && before.remaining_mines == after.remaining_mines {
+                return None
+            }
+
+            Some(Self {
+                cells,
+                status: after.status,
+                remaining_mines: after.remaining_mines
+            })
+        }
+    }
+
+    pub(crate) struct Restarting(Arc<AtomicUsize>);
+
+    impl Restarting {
+        fn new(restarts: Arc<AtomicUsize>) -> Self {
+            restarts.fetch_add(1, Ordering::Relaxed);
+            Self(restarts)
+        }
+    }
+
+    impl Drop for Restarting {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    // sent once a game's been won or lost, with the whole layout showing
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct GameOver {
+        pub state: GameState,
+        pub stats: GameStats
+    }
+
+    // every operation holds the write lock from start to finish, first click generation included,
+    // so concurrent moves always behave as if they'd happened one after another
+    // (a restart doesn't have to wait for generation to finish, generation gives up while one's waiting for the lock)
     pub struct AsyncMinsweeperGame<S: Solver + Send + Sync, OnWin: Fn() + Send + Sync, OnLose: Fn() + Send + Sync> {
         minsweeper_game: RwLock<MinsweeperGame<S, OnWin, OnLose>>,
-        generate_lock: Mutex<()>
-
+        changes: broadcast::Sender<Change>,
+        game_overs: Mutex<Vec<mpsc::UnboundedSender<GameOver>>>,
+        restarts: Arc<AtomicUsize>,
+        #[cfg(feature = "tokio")]
+        progress: Arc<watch::Sender<Generation>>
     }
 
-    impl<S: Solver + Send + Sync + Clone, OnWin: Fn() + Send + Sync, OnLose: Fn() + Send + Sync> AsyncMinsweeperGame<S, OnWin, OnLose> {
+    impl<S: Solver + Send + Sync + Clone + 'static, OnWin: Fn() + Send + Sync, OnLose: Fn() + Send + Sync> AsyncMinsweeperGame<S, OnWin, OnLose> {
+        const CHANGE_CAPACITY: usize = 256;
 
         pub fn new(board_size: BoardSize, on_win: OnWin, on_lose: OnLose) -> Self {
             Self {
                 minsweeper_game: MinsweeperGame::new(board_size, on_win, on_lose).into(),
-                generate_lock: Default::default(),
+                changes: broadcast::channel(Self::CHANGE_CAPACITY).0,
+                game_overs: Default::default(),
+                restarts: Default::default(),
+                #[cfg(feature = "tokio")]
+                progress: Arc::new(watch::Sender::default())
             }
         }
 
+        // how far along generating the first click's board is
+        #[cfg(feature = "tokio")]
+        pub fn generation(&self) -> watch::Receiver<Generation> {
+            self.progress.subscribe()
+        }
+
+        // counts as a restart waiting for the lock until it's dropped, which is what tells generation to give up
+        // whoever holds the lock can't tell when the restart turned up, so there's no flag to miss in between
+        pub(crate) fn restarting(&self) -> Restarting {
+            Restarting::new(self.restarts.clone())
+        }
+
+        // None if it got cancelled
+        #[cfg(feature = "tokio")]
+        async fn generate(&self, game: &MinsweeperGame<S, OnWin, OnLose>, point: Point) -> Option<GameState> {
+            let Some(solver) = game.solver.clone() else {
+                return Some(generate_game(game.board_size))
+            };
+
+            let restarts = self.restarts.clone();
+            let progress = self.progress.clone();
+            generate_solvable_game_off_thread(game.board_size, solver, point, move || restarts.load(Ordering::Relaxed) > 0, move |e| {
+                progress.send_replace(e);
+            }).await
+        }
+
+        // nowhere to hand the attempts off to, so they run right here and look out for restarts in between
+        #[cfg(not(feature = "tokio"))]
+        async fn generate(&self, game: &MinsweeperGame<S, OnWin, OnLose>, point: Point) -> Option<GameState> {
+            let Some(solver) = &game.solver else {
+                return Some(generate_game(game.board_size))
+            };
+
+            while self.restarts.load(Ordering::Relaxed) == 0 {
+                if let Some(state) = try_generate_solvable_game(game.board_size, solver, point) {
+                    return Some(state)
+                }
+            }
+            None
+        }
+
+        // a subscriber that falls more than a few hundred changes behind gets `Lagged` and should fetch the whole state again
+        pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+            self.changes.subscribe()
+        }
+
+        // nobody listening means there's no point copying the board to compare against
+        fn snapshot(&self, game: &MinsweeperGame<S, OnWin, OnLose>) -> Option<GameState> {
+            (self.changes.receiver_count() > 0)
+                    .then(|| game.player_gamestate().clone())
+        }
+
+        fn publish(&self, before: Option<GameState>, after: &GameState) {
+            if let Some(change) = before.and_then(|before| Change::between(&before, after)) {
+                let _ = self.changes.send(change);
+            }
+        }
+
+        // a channel that hears about every game that ends from now on, unbounded so none of them get dropped
+        // unlike `on_win` and `on_lose` whatever handles these runs outside the lock, so it can take as long as it likes
+        pub fn game_overs(&self) -> mpsc::UnboundedReceiver<GameOver> {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            self.game_overs.lock()
+                    .unwrap()
+                    .push(sender);
+            receiver
+        }
+
+        fn announce(&self, was: GameStatus, game: &MinsweeperGame<S, OnWin, OnLose>) {
+            let state = game.player_gamestate();
+            if state.status == was || !matches!(state.status, GameStatus::Won | GameStatus::Lost) {
+                return
+            }
+
+            let game_over = GameOver { state: state.clone(), stats: game.stats() };
+            self.game_overs.lock()
+                    .unwrap()
+                    .retain(|e| e.send(game_over.clone()).is_ok());
+        }
+
+        // for wrappers that need to look at the game and move on it without anyone else getting a move in between
+        pub(crate) async fn write(&self) -> RwLockWriteGuard<'_, MinsweeperGame<S, OnWin, OnLose>> {
+            self.minsweeper_game.write().await
+        }
+
+        pub(crate) fn apply(&self, game: &mut MinsweeperGame<S, OnWin, OnLose>,
+                 operation: impl FnOnce(&mut MinsweeperGame<S, OnWin, OnLose>) -> Result<&GameState, &GameState>) -> Result<GameState, GameState> {
+            let before = self.snapshot(game);
+            let was = game.player_gamestate().status;
+
+            let result = operation(game)
+                    .cloned()
+                    .map_err(Clone::clone);
+            self.publish(before, result.as_ref().unwrap_or_else(|e| e));
+            self.announce(was, game);
+            result
+        }
+
         pub async fn start(&self) -> GameState {
-            drop(self.generate_lock.lock().await);
-            Minsweeper::start(&mut *self.minsweeper_game.write().await)
-                    .clone()
+            let restarting = self.restarting();
+            let mut game = self.minsweeper_game.write().await;
+            drop(restarting);
+            self.apply(&mut game, |game| Ok(Minsweeper::start(game)))
+                    .unwrap_or_else(|e| e)
         }
 
         pub async fn start_with_solver(&self, solver: S) -> GameState {
-            drop(self.generate_lock.lock().await);
-            self.minsweeper_game.write()
-                    .await
-                    .start_with_solver(solver)
-                    .clone()
+            let restarting = self.restarting();
+            let mut game = self.minsweeper_game.write().await;
+            drop(restarting);
+            self.apply(&mut game, |game| Ok(game.start_with_solver(solver)))
+                    .unwrap_or_else(|e| e)
         }
 
         pub async fn gamestate(&self) -> GameState {
@@ -411,80 +744,68 @@ pub mod nonblocking {
                     .clone()
         }
 
+        pub async fn stats(&self) -> GameStats {
+            self.minsweeper_game.read()
+                    .await
+                    .stats()
+        }
+
+        pub async fn hint(&self) -> Option<Hint> {
+            self.minsweeper_game.write()
+                    .await
+                    .hint()
+        }
 
         pub async fn reveal(&self, point: Point) -> Result<GameState, GameState> {
-            drop(self.generate_lock.lock().await);
             let mut game = self.minsweeper_game.write().await;
-            if check_interact(&*game, point).is_err() {
+            self.reveal_locked(&mut game, point).await
+        }
+
+        // whoever gets the lock first with `first` still set generates the board, everyone else waits for it
+        pub(crate) async fn reveal_locked(&self, game: &mut MinsweeperGame<S, OnWin, OnLose>, point: Point) -> Result<GameState, GameState> {
+            if check_interact(game, point).is_err() {
                 return Err(game.player_gamestate().clone())
             }
 
-            if game.first {
-                game.first = false;
-
-
-                let solver = game.solver.clone();
-                let size = game.board_size;
-                drop(game);
-
-                let generate_guard = self.generate_lock.lock();
-                let gamestate = if let Some(solver) = solver {
-                    generate_solvable_game_async(size, &solver, point).await
-                } else {
-                    generate_game(size)
+            if game.game_state.status == GameStatus::AwaitingFirstClick {
+                let Some(gamestate) = self.generate(game, point).await else {
+                    return Err(game.player_gamestate().clone())
                 };
-                *self.minsweeper_game.write().await.gamestate_mut() = gamestate;
-                drop(generate_guard);
 
-                game = self.minsweeper_game.write().await;
+                *game.gamestate_mut() = gamestate;
             }
 
-            // let mut game = self.minsweeper_game.write().await;
-            Minsweeper::reveal(&mut *game, point)
-                    .cloned()
-                    .map_err(Clone::clone)
+            self.apply(game, |game| Minsweeper::reveal(game, point))
         }
 
-
         pub async fn clear_around(&self, point: Point) -> Result<GameState, GameState> {
-            drop(self.generate_lock.lock().await);
-            Minsweeper::clear_around(&mut *self.minsweeper_game.write().await, point)
-                    .cloned()
-                    .map_err(Clone::clone)
+            let mut game = self.minsweeper_game.write().await;
+            self.apply(&mut game, |game| Minsweeper::clear_around(game, point))
         }
 
         pub async fn set_flagged(&self, point: Point, flagged: bool) -> Result<GameState, GameState> {
-            drop(self.generate_lock.lock().await);
-            Minsweeper::set_flagged(&mut *self.minsweeper_game.write().await, point, flagged)
-                    .cloned()
-                    .map_err(Clone::clone)
+            let mut game = self.minsweeper_game.write().await;
+            self.apply(&mut game, |game| Minsweeper::set_flagged(game, point, flagged))
         }
 
         pub async fn toggle_flag(&self, point: Point) -> Result<GameState, GameState> {
-            drop(self.generate_lock.lock().await);
-            Minsweeper::toggle_flag(&mut *self.minsweeper_game.write().await, point)
-                    .cloned()
-                    .map_err(Clone::clone)
+            let mut game = self.minsweeper_game.write().await;
+            self.apply(&mut game, |game| Minsweeper::toggle_flag(game, point))
         }
 
+        // looks at the cell and acts on it under the same lock, so nobody can change it in between
         pub async fn left_click(&self, point: Point) -> Result<GameState, GameState> {
-            let game = self.minsweeper_game.read().await;
+            let mut game = self.minsweeper_game.write().await;
             if check_interact(&*game, point).is_err() {
-                return Err(game.gamestate().clone())
+                return Err(game.player_gamestate().clone())
             }
 
-            let cell = game.gamestate().board[point];
-
-            match cell {
+            match game.player_gamestate().board[point] {
                 Cell { cell_type: CellType::Safe(_), cell_state: CellState::Revealed } => {
-                    drop(game);
-                    self.clear_around(point).await
-                },
-                Cell { cell_state: CellState::Unknown, .. } => {
-                    drop(game);
-                    self.reveal(point).await
+                    self.apply(&mut game, |game| Minsweeper::clear_around(game, point))
                 },
-                _ => Err(game.gamestate().clone())
+                Cell { cell_state: CellState::Unknown, .. } => self.reveal_locked(&mut game, point).await,
+                _ => Err(game.player_gamestate().clone())
             }
         }
 
@@ -496,23 +817,15 @@ pub mod nonblocking {
 
 pub fn generate_solvable_game(board_size: BoardSize, solver: &dyn Solver, point: Point) -> GameState {
     loop {
-        let state = generate_game(board_size);
-
-        let mut game = SetMinsweeperGame::new(state.clone());
-        Minsweeper::reveal(&mut game, point)
-                .expect("should always be able to successfully reveal");
-
-        let result = solver.solve_game(&mut game);
-
-        if result == GameResult::Won {
-            return state;
+        if let Some(state) = try_generate_solvable_game(board_size, solver, point) {
+            return state
         }
     }
 }
 
 pub async fn generate_solvable_game_async<S: Solver + Send + Sync>(board_size: BoardSize, solver: &S, point: Point) -> GameState {
     loop {
-        let Some(state) = try_generate_solvable_game_async(board_size, solver, point).await else {
+        let Some(state) = try_generate_solvable_game(board_size, solver, point) else {
             #[cfg(feature = "tokio")]
             tokio::task::yield_now().await;
             continue
@@ -520,7 +833,8 @@ pub async fn generate_solvable_game_async<S: Solver + Send + Sync>(board_size: B
         return state
     }
 }
-async fn try_generate_solvable_game_async<S: Solver + Send + Sync>(board_size: BoardSize, solver: &S, point: Point) -> Option<GameState> {
+
+fn try_generate_solvable_game(board_size: BoardSize, solver: &dyn Solver, point: Point) -> Option<GameState> {
     let state = generate_game(board_size);
 
     let mut game = SetMinsweeperGame::new(state.clone());
@@ -536,21 +850,203 @@ async fn try_generate_solvable_game_async<S: Solver + Send + Sync>(board_size: B
     }
 }
 
+#[cfg(feature = "tokio")]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Generation {
+    #[default]
+    Idle,
+    Running { attempts: usize },
+    Done { attempts: usize },
+    Cancelled { attempts: usize }
+}
+
+// the attempts run on tokio's blocking pool so a slow solver can't hold up the runtime
+// `should_stop` gets asked before every attempt and gives up with None once it says yes, as does dropping this future
+#[cfg(feature = "tokio")]
+pub async fn generate_solvable_game_off_thread<S: Solver + Send + 'static>(board_size: BoardSize, solver: S, point: Point,
+                                                                         should_stop: impl Fn() -> bool + Send + 'static,
+                                                                         mut on_progress: impl FnMut(Generation) + Send + 'static) -> Option<GameState> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    struct CancelOnDrop(Arc<AtomicBool>);
+
+    impl Drop for CancelOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Relaxed)
+        }
+    }
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let _cancel_on_drop = CancelOnDrop(dropped.clone());
+
+    let generation = tokio::task::spawn_blocking(move || {
+        let mut attempts = 0;
+
+        while !dropped.load(Ordering::Relaxed) && !should_stop() {
+            on_progress(Generation::Running { attempts });
+            attempts += 1;
+
+            if let Some(state) = try_generate_solvable_game(board_size, &solver, point) {
+                on_progress(Generation::Done { attempts });
+                return Some(state)
+            }
+        }
+
+        on_progress(Generation::Cancelled { attempts });
+        None
+    });
+
+    match generation.await {
+        Ok(state) => state,
+        Err(error) => match error.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(_) => None
+        }
+    }
+}
+
+const MAX_REPAIRS: usize = 1000;
+
+// instead of throwing the whole board away when the solver gets stuck this moves a single mine
+// between the frontier and the untouched interior, then lets the solver carry on from where it got stuck
+pub fn generate_solvable_game_by_repair(board_size: BoardSize, solver: &dyn Solver, point: Point) -> GameState {
+    loop {
+        let mut progress = generate_game(board_size);
+
+        if progress.board[point].cell_type == CellType::Mine && !relocate_mine(&mut progress, point, point) {
+            continue
+        }
+
+        for _ in 0..MAX_REPAIRS {
+            let mut game = SetMinsweeperGame::new(progress.clone());
+            Minsweeper::reveal(&mut game, point)
+                    .expect("should always be able to try revealing the first click");
+
+            if solver.solve_game(&mut game) == GameResult::Won {
+                // the solver only saw the numbers around the repaired region change partway through,
+                // so check that it also gets through the final layout from the very beginning
+                let layout = strip_progress(&game.game_state);
+
+                let mut fresh = SetMinsweeperGame::new(layout.clone());
+                Minsweeper::reveal(&mut fresh, point)
+                        .expect("should always be able to try revealing the first click");
+
+                if solver.solve_game(&mut fresh) == GameResult::Won {
+                    return layout
+                }
+
+                game = fresh;
+            }
+
+            progress = game.game_state;
+
+            if !repair(&mut progress, point) {
+                break
+            }
+        }
+    }
+}
+
+fn strip_progress(state: &GameState) -> GameState {
+    let mut board = state.board.clone();
+
+    for cell in board.iter_mut() {
+        cell.cell_state = CellState::Unknown;
+    }
+
+    GameState::new(GameStatus::Playing, board, usize::from(state.board.size().mines()).try_into().unwrap())
+}
+
+fn repair(state: &mut GameState, start: Point) -> bool {
+    if state.status == GameStatus::Lost {
+        // whatever got revealed can't be trusted anymore, so start over around the first click
+        *state = strip_progress(state);
+
+        let mut region: Vec<_> = state.board.size().neighbours(start).collect();
+        region.push(start);
+
+        let mines: Vec<_> = region.into_iter()
+                .filter(|e| state.board[*e].cell_type == CellType::Mine)
+                .collect();
+
+        return !mines.is_empty() && relocate_mine(state, mines[fastrand::usize(..mines.len())], start)
+    }
+
+    let size = state.board.size();
+    let (frontier, interior): (Vec<_>, Vec<_>) = size.points()
+            .filter(|e| state.board[*e].cell_state == CellState::Unknown)
+            .partition(|e| size.neighbours(*e)
+                    .any(|e| state.board[e].cell_state == CellState::Revealed));
+
+    let is_mine = |point: &Point| state.board[*point].cell_type == CellType::Mine;
+
+    let frontier_mines: Vec<_> = frontier.iter().copied().filter(is_mine).collect();
+    let interior_safes: Vec<_> = interior.iter().copied().filter(|e| !is_mine(e) && *e != start).collect();
+
+    let (from, to) = if !frontier_mines.is_empty() && !interior_safes.is_empty() {
+        (frontier_mines, interior_safes)
+    } else {
+        let interior_mines: Vec<_> = interior.iter().copied().filter(is_mine).collect();
+        let frontier_safes: Vec<_> = frontier.iter().copied().filter(|e| !is_mine(e) && *e != start).collect();
+
+        if interior_mines.is_empty() || frontier_safes.is_empty() {
+            return false
+        }
+
+        (interior_mines, frontier_safes)
+    };
+
+    let from = from[fastrand::usize(..from.len())];
+    let to = to[fastrand::usize(..to.len())];
+
+    state.board[from].cell_type = CellType::EMPTY;
+    state.board[to].cell_type = CellType::Mine;
+    generate_nmbers(&mut state.board);
+
+    true
+}
+
+fn relocate_mine(state: &mut GameState, mine: Point, start: Point) -> bool {
+    let size = state.board.size();
+
+    let candidates: Vec<_> = size.points()
+            .filter(|e| state.board[*e].cell_type != CellType::Mine
+                    && state.board[*e].cell_state == CellState::Unknown
+                    && *e != start
+                    && !size.neighbours(start).any(|neighbour| neighbour == *e))
+            .collect();
+
+    if candidates.is_empty() {
+        return false
+    }
+
+    state.board[mine].cell_type = CellType::EMPTY;
+    state.board[candidates[fastrand::usize(..candidates.len())]].cell_type = CellType::Mine;
+    generate_nmbers(&mut state.board);
+
+    true
+}
+
 #[derive(Clone, Debug)]
 pub struct SetMinsweeperGame {
+    layout: GameState,
     game_state: GameState,
     player_game_state: GameState
 }
 
 impl SetMinsweeperGame {
     pub fn new(game_state: GameState) -> Self {
-        Self { player_game_state: game_state.hide_mines(), game_state }
+        Self { layout: game_state.clone(), player_game_state: game_state.hide_mines(), game_state }
     }
 }
 
 impl InternalMinsweeper for SetMinsweeperGame {
+    // starting over goes back to whatever it was made with
     fn start(&mut self) -> &GameState {
-        unimplemented!()
+        *self = Self::new(self.layout.clone());
+        &self.player_game_state
     }
 
     fn on_win(&self) {
@@ -0,0 +1,379 @@
+use crate::board::Point;
+use crate::{CellState, CellType, GameState, GameStatus, Minsweeper};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+// several players on one board, keeping track of who did what
+// with private flags every player gets their own layer of flags nobody else sees, and chords go by their own flags
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attribution<P: Eq + Hash> {
+    pub revealed_by: HashMap<Point, P>,
+    pub flagged_by: HashMap<Point, P>,
+    pub loser: Option<P>
+}
+
+impl<P: Eq + Hash> Default for Attribution<P> {
+    fn default() -> Self {
+        Self {
+            revealed_by: HashMap::new(),
+            flagged_by: HashMap::new(),
+            loser: None
+        }
+    }
+}
+
+struct Players<P: Eq + Hash> {
+    attribution: Attribution<P>,
+    layers: Option<HashMap<P, HashSet<Point>>>
+}
+
+impl<P: Eq + Hash + Clone> Players<P> {
+    fn new(private_flags: bool) -> Self {
+        Self {
+            attribution: Attribution::default(),
+            layers: private_flags.then(HashMap::new)
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.layers.is_some());
+    }
+
+    fn record(&mut self, player: &P, before: &GameState, after: &GameState) {
+        for point in after.board.size().points() {
+            let (was, is) = (before.board[point].cell_state, after.board[point].cell_state);
+
+            if was != CellState::Revealed && is == CellState::Revealed {
+                self.attribution.revealed_by.insert(point, player.clone());
+                self.attribution.flagged_by.remove(&point);
+                for layer in self.layers.iter_mut().flat_map(HashMap::values_mut) {
+                    layer.remove(&point);
+                }
+            } else if was != CellState::Flagged && is == CellState::Flagged {
+                self.attribution.flagged_by.insert(point, player.clone());
+            } else if was == CellState::Flagged && is != CellState::Flagged {
+                self.attribution.flagged_by.remove(&point);
+            }
+        }
+
+        if before.status.is_ongoing() && after.status == GameStatus::Lost {
+            self.attribution.loser = Some(player.clone());
+        }
+    }
+
+    // None if flags aren't private
+    fn set_private_flag(&mut self, player: &P, point: Point, flagged: Option<bool>, state: &GameState) -> Option<bool> {
+        let layer = self.layers.as_mut()?
+                .entry(player.clone())
+                .or_default();
+
+        if state.status != GameStatus::Playing || !is_on_board(state, point)
+                || state.board[point].cell_state != CellState::Unknown {
+            return Some(false)
+        }
+
+        match flagged.unwrap_or(!layer.contains(&point)) {
+            true => layer.insert(point),
+            false => layer.remove(&point)
+        };
+
+        Some(true)
+    }
+
+    // with private flags a chord has to count the player's own flags, so it turns into revealing the rest one by one
+    fn chord_targets(&self, player: &P, point: Point, state: &GameState) -> Option<Vec<Point>> {
+        let layers = self.layers.as_ref()?;
+        let empty = HashSet::new();
+        let layer = layers.get(player).unwrap_or(&empty);
+
+        let size = state.board.size();
+
+        if !is_revealed(state, point) {
+            return Some(vec![])
+        }
+        let CellType::Safe(number) = state.board[point].cell_type else { return Some(vec![]) };
+        if size.neighbours(point).filter(|e| layer.contains(e)).count() != number as usize {
+            return Some(vec![])
+        }
+
+        Some(size.neighbours(point)
+                .filter(|e| !layer.contains(e) && state.board[*e].cell_state == CellState::Unknown)
+                .collect())
+    }
+
+    fn view(&self, player: &P, state: &GameState) -> GameState {
+        let mut state = state.clone();
+
+        if let Some(layer) = self.layers.as_ref().and_then(|e| e.get(player)) {
+            for point in layer {
+                if state.board[*point].cell_state == CellState::Unknown {
+                    state.board[*point].cell_state = CellState::Flagged;
+                    state.remaining_mines -= 1;
+                }
+            }
+        }
+
+        state
+    }
+}
+
+fn is_on_board(state: &GameState, point: Point) -> bool {
+    point.0 < state.board.size().width().get() && point.1 < state.board.size().height().get()
+}
+
+fn is_revealed(state: &GameState, point: Point) -> bool {
+    is_on_board(state, point) && state.board[point].cell_state == CellState::Revealed
+}
+
+pub struct Coop<M: Minsweeper, P: Eq + Hash + Clone> {
+    game: M,
+    players: Players<P>
+}
+
+impl<M: Minsweeper, P: Eq + Hash + Clone> Coop<M, P> {
+    pub fn new(game: M) -> Self {
+        Self { game, players: Players::new(false) }
+    }
+
+    pub fn with_private_flags(game: M) -> Self {
+        Self { game, players: Players::new(true) }
+    }
+
+    pub fn game(&self) -> &M {
+        &self.game
+    }
+
+    pub fn into_inner(self) -> M {
+        self.game
+    }
+
+    pub fn attribution(&self) -> &Attribution<P> {
+        &self.players.attribution
+    }
+
+    // the board as this player sees it, private flags included
+    pub fn view(&self, player: &P) -> GameState {
+        self.players.view(player, self.game.gamestate())
+    }
+
+    pub fn start(&mut self) -> &GameState {
+        self.players.reset();
+        self.game.start()
+    }
+
+    pub fn reveal(&mut self, player: &P, point: Point) -> Result<GameState, GameState> {
+        self.play(player, |game| game.reveal(point).is_ok())
+    }
+
+    pub fn clear_around(&mut self, player: &P, point: Point) -> Result<GameState, GameState> {
+        match self.players.chord_targets(player, point, self.game.gamestate()) {
+            Some(targets) => self.play(player, |game| {
+                let success = !targets.is_empty();
+                for target in targets {
+                    if game.reveal(target).is_err() || game.gamestate().status != GameStatus::Playing {
+                        break
+                    }
+                }
+                success
+            }),
+            None => self.play(player, |game| game.clear_around(point).is_ok())
+        }
+    }
+
+    pub fn set_flagged(&mut self, player: &P, point: Point, flagged: bool) -> Result<GameState, GameState> {
+        self.flag(player, point, Some(flagged))
+    }
+
+    pub fn toggle_flag(&mut self, player: &P, point: Point) -> Result<GameState, GameState> {
+        self.flag(player, point, None)
+    }
+
+    pub fn left_click(&mut self, player: &P, point: Point) -> Result<GameState, GameState> {
+        if is_revealed(self.game.gamestate(), point) {
+            self.clear_around(player, point)
+        } else {
+            self.reveal(player, point)
+        }
+    }
+
+    pub fn right_click(&mut self, player: &P, point: Point) -> Result<GameState, GameState> {
+        self.toggle_flag(player, point)
+    }
+
+    fn flag(&mut self, player: &P, point: Point, flagged: Option<bool>) -> Result<GameState, GameState> {
+        match self.players.set_private_flag(player, point, flagged, self.game.gamestate()) {
+            Some(success) => self.result(player, success),
+            None => self.play(player, |game| match flagged {
+                Some(flagged) => game.set_flagged(point, flagged).is_ok(),
+                None => game.toggle_flag(point).is_ok()
+            })
+        }
+    }
+
+    fn play(&mut self, player: &P, play: impl FnOnce(&mut M) -> bool) -> Result<GameState, GameState> {
+        let before = self.game.gamestate().clone();
+        let success = play(&mut self.game);
+        self.players.record(player, &before, self.game.gamestate());
+
+        self.result(player, success)
+    }
+
+    fn result(&self, player: &P, success: bool) -> Result<GameState, GameState> {
+        let view = self.view(player);
+        if success { Ok(view) } else { Err(view) }
+    }
+}
+
+#[cfg(feature = "async")]
+pub mod nonblocking {
+    use crate::board::Point;
+    use crate::minsweeper::coop::{is_revealed, Attribution, Players};
+    use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+    use crate::minsweeper::{InternalMinsweeper, MinsweeperGame};
+    use crate::solver::Solver;
+    use crate::{GameState, GameStatus, Minsweeper};
+    use std::hash::Hash;
+    use tokio::sync::Mutex;
+
+    // every move takes the players lock and then the game's, and keeps both until it's done,
+    // so the before and after of each player's move line up and nobody else gets a move in between
+    // (moves made straight through `game()` still happen, they just aren't credited to anyone)
+    // restarts let the game know they're coming before waiting on anything, so a move generating the first board gives up for them
+    pub struct AsyncCoop<S: Solver + Send + Sync, OnWin: Fn() + Send + Sync, OnLose: Fn() + Send + Sync, P: Eq + Hash + Clone> {
+        game: AsyncMinsweeperGame<S, OnWin, OnLose>,
+        players: Mutex<Players<P>>
+    }
+
+    impl<S: Solver + Send + Sync + Clone + 'static, OnWin: Fn() + Send + Sync, OnLose: Fn() + Send + Sync, P: Eq + Hash + Clone> AsyncCoop<S, OnWin, OnLose, P> {
+        pub fn new(game: AsyncMinsweeperGame<S, OnWin, OnLose>) -> Self {
+            Self { game, players: Mutex::new(Players::new(false)) }
+        }
+
+        pub fn with_private_flags(game: AsyncMinsweeperGame<S, OnWin, OnLose>) -> Self {
+            Self { game, players: Mutex::new(Players::new(true)) }
+        }
+
+        pub fn game(&self) -> &AsyncMinsweeperGame<S, OnWin, OnLose> {
+            &self.game
+        }
+
+        pub async fn attribution(&self) -> Attribution<P> {
+            self.players.lock()
+                    .await
+                    .attribution
+                    .clone()
+        }
+
+        pub async fn view(&self, player: &P) -> GameState {
+            let players = self.players.lock().await;
+            players.view(player, &self.game.gamestate().await)
+        }
+
+        pub async fn start(&self) -> GameState {
+            let _restarting = self.game.restarting();
+            let mut players = self.players.lock().await;
+            players.reset();
+            self.game.start().await
+        }
+
+        pub async fn start_with_solver(&self, solver: S) -> GameState {
+            let _restarting = self.game.restarting();
+            let mut players = self.players.lock().await;
+            players.reset();
+            self.game.start_with_solver(solver).await
+        }
+
+        pub async fn reveal(&self, player: &P, point: Point) -> Result<GameState, GameState> {
+            let mut players = self.players.lock().await;
+            let mut game = self.game.write().await;
+
+            self.reveal_locked(&mut players, &mut game, player, point).await
+        }
+
+        pub async fn clear_around(&self, player: &P, point: Point) -> Result<GameState, GameState> {
+            let mut players = self.players.lock().await;
+            let mut game = self.game.write().await;
+
+            self.clear_around_locked(&mut players, &mut game, player, point).await
+        }
+
+        pub async fn set_flagged(&self, player: &P, point: Point, flagged: bool) -> Result<GameState, GameState> {
+            self.flag(player, point, Some(flagged)).await
+        }
+
+        pub async fn toggle_flag(&self, player: &P, point: Point) -> Result<GameState, GameState> {
+            self.flag(player, point, None).await
+        }
+
+        // looks at the cell and acts on it without letting go of either lock, so nobody can change it in between
+        pub async fn left_click(&self, player: &P, point: Point) -> Result<GameState, GameState> {
+            let mut players = self.players.lock().await;
+            let mut game = self.game.write().await;
+
+            if is_revealed(game.player_gamestate(), point) {
+                self.clear_around_locked(&mut players, &mut game, player, point).await
+            } else {
+                self.reveal_locked(&mut players, &mut game, player, point).await
+            }
+        }
+
+        pub async fn right_click(&self, player: &P, point: Point) -> Result<GameState, GameState> {
+            self.toggle_flag(player, point).await
+        }
+
+        async fn reveal_locked(&self, players: &mut Players<P>, game: &mut MinsweeperGame<S, OnWin, OnLose>, player: &P, point: Point) -> Result<GameState, GameState> {
+            let before = game.player_gamestate().clone();
+
+            let result = self.game.reveal_locked(game, point).await;
+            Self::finish(players, player, &before, result)
+        }
+
+        async fn clear_around_locked(&self, players: &mut Players<P>, game: &mut MinsweeperGame<S, OnWin, OnLose>, player: &P, point: Point) -> Result<GameState, GameState> {
+            let before = game.player_gamestate().clone();
+
+            let Some(targets) = players.chord_targets(player, point, &before) else {
+                let result = self.game.apply(game, |game| Minsweeper::clear_around(game, point));
+                return Self::finish(players, player, &before, result)
+            };
+
+            let success = !targets.is_empty();
+            for target in targets {
+                if self.game.reveal_locked(game, target).await.is_err() || game.player_gamestate().status != GameStatus::Playing {
+                    break
+                }
+            }
+
+            let after = game.player_gamestate().clone();
+            Self::finish(players, player, &before, if success { Ok(after) } else { Err(after) })
+        }
+
+        async fn flag(&self, player: &P, point: Point, flagged: Option<bool>) -> Result<GameState, GameState> {
+            let mut players = self.players.lock().await;
+            let mut game = self.game.write().await;
+            let before = game.player_gamestate().clone();
+
+            if let Some(success) = players.set_private_flag(player, point, flagged, &before) {
+                let view = players.view(player, &before);
+                return if success { Ok(view) } else { Err(view) }
+            }
+
+            let result = self.game.apply(&mut game, |game| match flagged {
+                Some(flagged) => Minsweeper::set_flagged(game, point, flagged),
+                None => Minsweeper::toggle_flag(game, point)
+            });
+            Self::finish(&mut players, player, &before, result)
+        }
+
+        fn finish(players: &mut Players<P>, player: &P, before: &GameState, result: Result<GameState, GameState>) -> Result<GameState, GameState> {
+            let success = result.is_ok();
+            let after = result.unwrap_or_else(|e| e);
+
+            players.record(player, before, &after);
+
+            let view = players.view(player, &after);
+            if success { Ok(view) } else { Err(view) }
+        }
+    }
+}
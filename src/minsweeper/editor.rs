@@ -0,0 +1,146 @@
+use crate::board::{Board, BoardSize, BoardSizeError, Point};
+use crate::minsweeper::position::{Position, PositionError};
+use crate::minsweeper::{generate_nmbers, InternalMinsweeper, SetMinsweeperGame};
+use crate::solver::{GameResult, Solver};
+use crate::{Cell, CellState, CellType, GameState, GameStatus, Minsweeper};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+// for putting puzzles together by hand, the numbers always match wherever the mines are
+// cells that are revealed here are already open when the puzzle starts
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Editor {
+    board: Board
+}
+
+// how a solver got on with the puzzle, `state` is where it won, lost or got stuck
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Playthrough {
+    pub result: GameResult,
+    pub state: GameState
+}
+
+// the board's size and mine count have to work, and what's revealed has to be somewhere a game could actually get to
+#[derive(Debug)]
+pub enum EditorError {
+    BoardSize(BoardSizeError),
+    Position(PositionError)
+}
+
+impl Display for EditorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditorError::BoardSize(error) => write!(f, "{}", error),
+            EditorError::Position(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl Error for EditorError {}
+
+impl Editor {
+    pub fn new(width: usize, height: usize) -> Result<Self, BoardSizeError> {
+        Ok(Self { board: Board::empty(BoardSize::new(width, height, 1)?) })
+    }
+
+    // keeps the mines and whatever's revealed, flags don't mean anything in a layout
+    pub fn from_layout(state: &GameState) -> Self {
+        let mut board = state.board.clone();
+
+        for cell in board.iter_mut() {
+            if cell.cell_type != CellType::Mine {
+                cell.cell_type = CellType::EMPTY;
+            }
+            if cell.cell_state == CellState::Flagged {
+                cell.cell_state = CellState::Unknown;
+            }
+        }
+        generate_nmbers(&mut board);
+
+        Self { board }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn mines(&self) -> usize {
+        self.board.iter()
+                .filter(|e| e.cell_type == CellType::Mine)
+                .count()
+    }
+
+    fn is_on_board(&self, point: Point) -> bool {
+        point.0 < self.board.size().width().get() && point.1 < self.board.size().height().get()
+    }
+
+    // false if there's no such cell, placing a mine on a revealed cell hides it again
+    pub fn set_mine(&mut self, point: Point, mine: bool) -> bool {
+        if !self.is_on_board(point) {
+            return false
+        }
+
+        self.board[point] = match mine {
+            true => Cell::new(CellType::Mine, CellState::Unknown),
+            false => Cell::new(CellType::EMPTY, self.board[point].cell_state)
+        };
+        generate_nmbers(&mut self.board);
+
+        true
+    }
+
+    pub fn toggle_mine(&mut self, point: Point) -> bool {
+        self.is_on_board(point) && self.set_mine(point, self.board[point].cell_type != CellType::Mine)
+    }
+
+    // false if there's no such cell or it's a mine, mines can't start out revealed
+    // revealing a zero opens up everything around it the same as clicking it would
+    pub fn set_revealed(&mut self, point: Point, revealed: bool) -> bool {
+        if !self.is_on_board(point) || self.board[point].cell_type == CellType::Mine {
+            return false
+        }
+
+        match revealed {
+            true if self.board[point].cell_type == CellType::EMPTY => SetMinsweeperGame::reveal_empty(&mut self.board, point),
+            true => self.board[point].cell_state = CellState::Revealed,
+            false => self.board[point].cell_state = CellState::Unknown
+        }
+        true
+    }
+
+    // the puzzle as a position, fails if it has no mines or too many,
+    // or if hiding cells or moving mines has left a zero showing with something unopened around it
+    pub fn position(&self) -> Result<Position, EditorError> {
+        let size = self.board.size();
+        let mut board = Board::empty(BoardSize::new(size.width().get(), size.height().get(), self.mines())
+                .map_err(EditorError::BoardSize)?);
+
+        for point in size.points() {
+            board[point].cell_type = self.board[point].cell_type;
+        }
+
+        let revealed = size.points()
+                .filter(|e| self.board[*e].cell_state == CellState::Revealed);
+
+        Position::new(&GameState::new(GameStatus::Playing, board, 0), revealed, [])
+                .map_err(EditorError::Position)
+    }
+
+    pub fn layout(&self) -> Result<GameState, EditorError> {
+        Ok(self.position()?.state().clone())
+    }
+
+    pub fn game(&self) -> Result<SetMinsweeperGame, EditorError> {
+        Ok(SetMinsweeperGame::from_position(self.position()?))
+    }
+
+    // lets the solver loose on the puzzle as a player would see it, starting from the revealed cells
+    pub fn check(&self, solver: &dyn Solver) -> Result<Playthrough, EditorError> {
+        let mut game = self.game()?;
+        let result = solver.solve_game(&mut game);
+
+        Ok(Playthrough { result, state: game.gamestate().clone() })
+    }
+}
@@ -0,0 +1,128 @@
+use crate::board::{Board, BoardSize, BoardSizeError, Point};
+use crate::minsweeper::{generate_nmbers, InternalMinsweeper, MinsweeperGame, SetMinsweeperGame};
+use crate::solver::Solver;
+use crate::{CellState, CellType, GameState, GameStatus};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+// a game part way through: where the mines are, plus what's been opened and flagged so far
+// flags don't have to be right, players get them wrong too
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    state: GameState
+}
+
+#[derive(Debug)]
+pub enum PositionError {
+    BoardSize(BoardSizeError),
+    OffBoard(Point),
+    UnknownCell(Point),
+    RevealedMine(Point),
+    RevealedAndFlagged(Point),
+    UnopenedZero(Point)
+}
+
+impl Display for PositionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionError::BoardSize(error) =>
+                write!(f, "{}", error),
+            PositionError::OffBoard(point) =>
+                write!(f, "{:?} isn't on the board", point),
+            PositionError::UnknownCell(point) =>
+                write!(f, "the layout doesn't say whether {:?} is a mine", point),
+            PositionError::RevealedMine(point) =>
+                write!(f, "{:?} is a mine, so it can't be revealed", point),
+            PositionError::RevealedAndFlagged(point) =>
+                write!(f, "{:?} can't be both revealed and flagged", point),
+            PositionError::UnopenedZero(point) =>
+                write!(f, "{:?} is revealed with no mines around it, so everything around it should be too", point)
+        }
+    }
+}
+
+impl Error for PositionError {}
+
+impl Position {
+    // the layout's own cell states are ignored, and its numbers are worked out again from the mines
+    // so is its mine count, a hand-made layout might not have as many as its size says
+    pub fn new(layout: &GameState, revealed: impl IntoIterator<Item = Point>, flagged: impl IntoIterator<Item = Point>) -> Result<Self, PositionError> {
+        let size = layout.board.size();
+        let is_on_board = |point: Point| point.0 < size.width().get() && point.1 < size.height().get();
+
+        if let Some(point) = size.points().find(|e| layout.board[*e].cell_type == CellType::Unknown) {
+            return Err(PositionError::UnknownCell(point))
+        }
+
+        let mines = layout.board.iter()
+                .filter(|e| e.cell_type == CellType::Mine)
+                .count();
+        let mut board = Board::empty(BoardSize::new(size.width().get(), size.height().get(), mines)
+                .map_err(PositionError::BoardSize)?);
+        for point in size.points() {
+            board[point].cell_type = layout.board[point].cell_type;
+        }
+        generate_nmbers(&mut board);
+
+        let revealed: HashSet<Point> = revealed.into_iter().collect();
+        let flagged: HashSet<Point> = flagged.into_iter().collect();
+
+        for point in revealed.iter().chain(&flagged) {
+            if !is_on_board(*point) {
+                return Err(PositionError::OffBoard(*point))
+            }
+        }
+
+        for point in &revealed {
+            if board[*point].cell_type == CellType::Mine {
+                return Err(PositionError::RevealedMine(*point))
+            }
+            if flagged.contains(point) {
+                return Err(PositionError::RevealedAndFlagged(*point))
+            }
+            // revealing an empty cell always opens up everything around it
+            if board[*point].cell_type == CellType::EMPTY && size.neighbours(*point).any(|e| !revealed.contains(&e)) {
+                return Err(PositionError::UnopenedZero(*point))
+            }
+
+            board[*point].cell_state = CellState::Revealed;
+        }
+        for point in &flagged {
+            board[*point].cell_state = CellState::Flagged;
+        }
+
+        let status = if board.has_won() { GameStatus::Won } else { GameStatus::Playing };
+
+        Ok(Self { state: GameState::new(status, board, mines as isize - flagged.len() as isize) })
+    }
+
+    // the whole position, mines and all
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+}
+
+impl SetMinsweeperGame {
+    pub fn from_position(position: Position) -> Self {
+        Self::new(position.state)
+    }
+}
+
+impl<S: Solver, OnWin: Fn(), OnLose: Fn()> MinsweeperGame<S, OnWin, OnLose> {
+    // skips the first click, the board is already there
+    // the game's own size and solver are left alone, so restarting afterwards goes back to boards like it had before
+    // a position that's already been won counts as winning straight away
+    pub fn start_from_position(&mut self, position: Position) -> &GameState {
+        self.stats = Default::default();
+        let won = position.state.status == GameStatus::Won;
+        *self.gamestate_mut() = position.state;
+
+        if won {
+            self.on_win();
+        }
+
+        self.player_gamestate()
+    }
+}
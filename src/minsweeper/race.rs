@@ -0,0 +1,146 @@
+use crate::board::{BoardSize, Point};
+use crate::minsweeper::{generate_game_from_seed_around, SetMinsweeperGame};
+use crate::solver::{Action, Actionable};
+use crate::{CellState, CellType, GameState, GameStatus, Minsweeper};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+// everyone plays their own copy of the same layout, the first to clear it wins
+// (anything already revealed in the layout is revealed for everyone)
+pub struct Race<P: Eq + Hash + Clone> {
+    layout: GameState,
+    started: Option<Instant>,
+    racers: HashMap<P, SetMinsweeperGame>,
+    finishes: Vec<Finish<P>>
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Finish<P> {
+    pub player: P,
+    pub status: GameStatus,
+    pub time: Duration
+}
+
+impl<P: Eq + Hash + Clone> Race<P> {
+    pub fn new(layout: GameState) -> Self {
+        Self {
+            layout,
+            started: None,
+            racers: HashMap::new(),
+            finishes: vec![]
+        }
+    }
+
+    // nobody has to guess where to start, `start` is opened up for everyone before the race begins
+    // None if `start` is off the board, or the board's so full that opening it leaves nothing to race over
+    pub fn from_seed(board_size: BoardSize, seed: u64, start: Point) -> Option<Self> {
+        let mut game = SetMinsweeperGame::new(generate_game_from_seed_around(board_size, seed, start));
+
+        if game.reveal(start).ok()?.status != GameStatus::Playing {
+            return None
+        }
+
+        Some(Self::new(game.game_state))
+    }
+
+    pub fn layout(&self) -> &GameState {
+        &self.layout
+    }
+
+    // false if they were already in
+    pub fn join(&mut self, player: P) -> bool {
+        if self.racers.contains_key(&player) {
+            return false
+        }
+
+        self.racers.insert(player, SetMinsweeperGame::new(self.layout.clone()));
+        true
+    }
+
+    // starts the clock, nobody can make a move before this
+    pub fn start(&mut self) {
+        self.started.get_or_insert_with(Instant::now);
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started.is_some()
+    }
+
+    // a race nobody's in isn't over, it just hasn't got going
+    pub fn is_over(&self) -> bool {
+        !self.racers.is_empty() && self.finishes.len() == self.racers.len()
+    }
+
+    pub fn gamestate(&self, player: &P) -> Option<&GameState> {
+        self.racers.get(player)
+                .map(Minsweeper::gamestate)
+    }
+
+    // None if the player isn't in the race or it hasn't started yet
+    pub fn action(&mut self, player: &P, action: Action) -> Option<Result<&GameState, &GameState>> {
+        self.play(player, |game| game.action(action).is_ok())
+    }
+
+    pub fn left_click(&mut self, player: &P, point: Point) -> Option<Result<&GameState, &GameState>> {
+        self.play(player, |game| game.left_click(point).is_ok())
+    }
+
+    pub fn right_click(&mut self, player: &P, point: Point) -> Option<Result<&GameState, &GameState>> {
+        self.play(player, |game| game.right_click(point).is_ok())
+    }
+
+    fn play(&mut self, player: &P, play: impl FnOnce(&mut SetMinsweeperGame) -> bool) -> Option<Result<&GameState, &GameState>> {
+        let started = self.started?;
+        let game = self.racers.get_mut(player)?;
+
+        let success = play(game);
+        let status = game.gamestate().status;
+
+        if status.is_over() && !self.finishes.iter().any(|e| e.player == *player) {
+            self.finishes.push(Finish {
+                player: player.clone(),
+                status,
+                time: started.elapsed()
+            });
+        }
+
+        let state = self.racers[player].gamestate();
+        Some(if success { Ok(state) } else { Err(state) })
+    }
+
+    // how much of the board's safe cells the player has revealed, from 0 to 1
+    pub fn progress(&self, player: &P) -> Option<f64> {
+        let board = &self.racers.get(player)?.gamestate().board;
+
+        let safe = self.layout.board.iter()
+                .filter(|e| matches!(e.cell_type, CellType::Safe(_)))
+                .count();
+        let revealed = board.iter()
+                .filter(|e| e.cell_state == CellState::Revealed && matches!(e.cell_type, CellType::Safe(_)))
+                .count();
+
+        Some(revealed as f64 / safe.max(1) as f64)
+    }
+
+    // everyone in the race, furthest along first
+    pub fn standings(&self) -> Vec<(P, f64)> {
+        let mut standings: Vec<_> = self.racers.keys()
+                .map(|e| (e.clone(), self.progress(e).unwrap_or_default()))
+                .collect();
+
+        standings.sort_by(|a, b| b.1.total_cmp(&a.1));
+        standings
+    }
+
+    // in the order they finished, whether they won or not
+    pub fn finishes(&self) -> &[Finish<P>] {
+        &self.finishes
+    }
+
+    pub fn winner(&self) -> Option<&Finish<P>> {
+        self.finishes.iter()
+                .find(|e| e.status == GameStatus::Won)
+    }
+}
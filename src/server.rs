@@ -0,0 +1,191 @@
+use crate::board::{BoardSize, Point};
+use crate::minsweeper::nonblocking::{AsyncMinsweeperGame, Change};
+use crate::solver::Solver;
+use crate::{Cell, GameState, GameStatus};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, Mutex};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+// clients connect to ws://host/<room name>, everyone on the same path shares one game
+// everything sent out comes from the player's view, the real layout only shows up once the game is over
+
+pub type SharedSolver = Arc<dyn Solver + Send + Sync>;
+type RoomGame = AsyncMinsweeperGame<SharedSolver, fn(), fn()>;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Reveal { point: Point },
+    Flag { point: Point },
+    Chord { point: Point },
+    Restart
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    State { state: GameState },
+    Diff { status: GameStatus, remaining_mines: isize, cells: Vec<(Point, Cell)> },
+    Rejected { message: ClientMessage }
+}
+
+pub struct Room {
+    game: RoomGame,
+    solver: Option<SharedSolver>
+}
+
+impl Room {
+    async fn new(board_size: BoardSize, solver: Option<SharedSolver>) -> Self {
+        let room = Self {
+            game: AsyncMinsweeperGame::new(board_size, (|| {}) as fn(), (|| {}) as fn()),
+            solver
+        };
+        room.restart().await;
+        room
+    }
+
+    async fn restart(&self) -> GameState {
+        match &self.solver {
+            Some(solver) => self.game.start_with_solver(solver.clone()).await,
+            None => self.game.start().await
+        }
+    }
+
+    pub async fn gamestate(&self) -> GameState {
+        self.game.gamestate().await
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+        self.game.subscribe()
+    }
+
+    // returns false if the move wasn't allowed, nobody else hears about those
+    pub async fn apply(&self, message: ClientMessage) -> bool {
+        match message {
+            ClientMessage::Reveal { point } => self.game.reveal(point).await.is_ok(),
+            ClientMessage::Flag { point } => self.game.toggle_flag(point).await.is_ok(),
+            ClientMessage::Chord { point } => self.game.clear_around(point).await.is_ok(),
+            ClientMessage::Restart => {
+                self.restart().await;
+                true
+            }
+        }
+    }
+}
+
+pub struct Server {
+    board_size: BoardSize,
+    solver: Option<SharedSolver>,
+    rooms: Mutex<HashMap<String, Arc<Room>>>
+}
+
+impl Server {
+    pub fn new(board_size: BoardSize) -> Self {
+        Self {
+            board_size,
+            solver: None,
+            rooms: Mutex::new(HashMap::new())
+        }
+    }
+
+    // every new room's first click gets a board this solver can win
+    pub fn with_solver(mut self, solver: impl Solver + Send + Sync + 'static) -> Self {
+        self.solver = Some(Arc::new(solver));
+        self
+    }
+
+    pub async fn room(&self, name: &str) -> Arc<Room> {
+        let mut rooms = self.rooms.lock().await;
+
+        if let Some(room) = rooms.get(name) {
+            return room.clone()
+        }
+
+        let room = Arc::new(Room::new(self.board_size, self.solver.clone()).await);
+        rooms.insert(name.to_string(), room.clone());
+        room
+    }
+
+    // the room's only kept around while someone's in it, so paths nobody's on don't pile up
+    async fn leave(&self, name: &str, room: Arc<Room>) {
+        let mut rooms = self.rooms.lock().await;
+        drop(room);
+
+        if rooms.get(name).is_some_and(|e| Arc::strong_count(e) == 1) {
+            rooms.remove(name);
+        }
+    }
+
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> std::io::Result<()> {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(self.clone().connect(stream));
+                }
+                // one bad connection (or running out of file descriptors for a bit) shouldn't take everyone else down
+                Err(e) => {
+                    eprintln!("couldn't accept a connection: {e}");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    }
+
+    // the handshake callback's signature isn't ours to pick
+    #[allow(clippy::result_large_err)]
+    async fn connect(self: Arc<Self>, stream: TcpStream) {
+        let mut path = String::new();
+        let Ok(socket) = tokio_tungstenite::accept_hdr_async(stream, |request: &Request, response: Response| {
+            path = request.uri().path().trim_start_matches('/').to_string();
+            Ok(response)
+        }).await else {
+            return
+        };
+
+        let room = self.room(&path).await;
+        Self::play(&room, socket).await;
+        self.leave(&path, room).await;
+    }
+
+    async fn play(room: &Room, socket: WebSocketStream<TcpStream>) {
+        let mut updates = room.subscribe();
+        let (mut sink, mut stream) = socket.split();
+
+        let mut outgoing = vec![ServerMessage::State { state: room.gamestate().await }];
+
+        loop {
+            for message in outgoing.drain(..) {
+                let Ok(text) = serde_json::to_string(&message) else { continue };
+                if sink.send(Message::Text(text.into())).await.is_err() {
+                    return
+                }
+            }
+
+            tokio::select! {
+                incoming = stream.next() => match incoming {
+                    Some(Ok(Message::Text(text))) => match serde_json::from_str::<ClientMessage>(&text) {
+                        Ok(message) => if !room.apply(message.clone()).await {
+                            outgoing.push(ServerMessage::Rejected { message })
+                        }
+                        Err(_) => continue
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => continue
+                },
+                update = updates.recv() => match update {
+                    Ok(Change { cells, status, remaining_mines }) => outgoing.push(ServerMessage::Diff { status, remaining_mines, cells }),
+                    // fell too far behind to patch things up, so just start over from the whole board
+                    Err(RecvError::Lagged(_)) => outgoing.push(ServerMessage::State { state: room.gamestate().await }),
+                    Err(RecvError::Closed) => return
+                }
+            }
+        }
+    }
+}
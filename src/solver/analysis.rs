@@ -0,0 +1,91 @@
+use crate::board::Point;
+use crate::solver::frontier::{Flags, Frontier};
+use crate::{Cell, CellState, CellType, GameState};
+use std::collections::HashSet;
+
+// everything here is worked out from the player's view alone, flags are treated as the player's guesses and not as facts
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Analysis {
+    pub wrong_flags: HashSet<Point>,
+    pub broken_numbers: HashSet<Point>,
+    pub safe: HashSet<Point>,
+    pub mines: HashSet<Point>,
+    pub complete: bool
+}
+
+pub fn analyse(state: &GameState) -> Analysis {
+    let board = &state.board;
+    let size = board.size();
+
+    let broken_numbers = size.points()
+            .filter(|point| {
+                let Cell { cell_type: CellType::Safe(number), cell_state: CellState::Revealed } = board[*point] else {
+                    return false
+                };
+
+                let flags = size.neighbours(*point)
+                        .filter(|e| board[*e].cell_state == CellState::Flagged)
+                        .count();
+                let unknowns = size.neighbours(*point)
+                        .filter(|e| board[*e].cell_state == CellState::Unknown)
+                        .count();
+
+                flags > number as usize || flags + unknowns < number as usize
+            })
+            .collect();
+
+    let deductions = Frontier::new(state, Flags::Ignore)
+            .deduce(Frontier::SEARCH_BUDGET);
+
+    let (wrong_flags, safe) = deductions.safe.into_iter()
+            .partition(|e| board[*e].cell_state == CellState::Flagged);
+    let mines = deductions.mines.into_iter()
+            .filter(|e| board[*e].cell_state == CellState::Unknown)
+            .collect();
+
+    Analysis {
+        wrong_flags,
+        broken_numbers,
+        safe,
+        mines,
+        complete: deductions.complete
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Uniqueness {
+    // every hidden cell is either a mine in all the layouts that fit or in none of them
+    Unique,
+    // the cells that are mines in some layouts that fit and safe in others
+    Ambiguous(HashSet<Point>),
+    // no layout fits the numbers at all
+    Inconsistent,
+    // too many layouts to go through them all
+    Undecided
+}
+
+// whether the numbers showing pin down where every mine is, checked against every layout that fits them
+// like `analyse` this doesn't take the player's flags at their word
+pub fn uniqueness(state: &GameState) -> Uniqueness {
+    let frontier = Frontier::new(state, Flags::Ignore);
+    let tallies = frontier.tallies(Frontier::SEARCH_BUDGET);
+
+    if frontier.feasible_totals(&tallies).is_none() {
+        return Uniqueness::Inconsistent
+    }
+
+    let deductions = frontier.deduce_from(&tallies);
+    if !deductions.complete {
+        return Uniqueness::Undecided
+    }
+
+    let ambiguous: HashSet<Point> = frontier.cells.iter()
+            .chain(&frontier.interior)
+            .filter(|e| !deductions.safe.contains(e) && !deductions.mines.contains(e))
+            .copied()
+            .collect();
+
+    if ambiguous.is_empty() { Uniqueness::Unique } else { Uniqueness::Ambiguous(ambiguous) }
+}
@@ -0,0 +1,473 @@
+use crate::board::Point;
+use crate::{Cell, CellState, CellType, GameState};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// the player's view of a board as a set of linear constraints over the unknown cells:
+// every revealed number says exactly how many of its unknown neighbours are mines,
+// and every unknown cell together holds exactly `mines` mines
+
+// a solver can trust its own flags, but a player's flags are only guesses so they count as unknown cells
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Flags {
+    Trust,
+    Ignore
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Constraint {
+    pub vars: Vec<usize>,
+    pub mines: isize
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Component {
+    pub vars: Vec<usize>,
+    pub constraints: Vec<usize>
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Frontier {
+    pub cells: Vec<Point>,
+    pub constraints: Vec<Constraint>,
+    pub components: Vec<Component>,
+    pub interior: Vec<Point>,
+    pub mines: isize
+}
+
+// how many layouts of a component use a given number of mines, and how many of those have a mine in each cell
+#[derive(Clone, Debug)]
+pub(crate) struct Tally {
+    pub count: u128,
+    pub mines: Vec<u128>
+}
+
+pub(crate) type Tallies = BTreeMap<usize, Tally>;
+
+// every layout of a component, by how many mines it uses
+pub(crate) type Found = BTreeMap<usize, Vec<Vec<bool>>>;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Deductions {
+    pub safe: HashSet<Point>,
+    pub mines: HashSet<Point>,
+    pub complete: bool
+}
+
+impl Frontier {
+    pub const SEARCH_BUDGET: usize = 1 << 20;
+
+    pub fn new(state: &GameState, flags: Flags) -> Self {
+        let board = &state.board;
+        let size = board.size();
+
+        let is_unknown = |point: Point| match board[point].cell_state {
+            CellState::Unknown => true,
+            CellState::Flagged => flags == Flags::Ignore,
+            CellState::Revealed => false
+        };
+        let is_known_mine = |point: Point| !is_unknown(point)
+                && (board[point].cell_state == CellState::Flagged || board[point].cell_type == CellType::Mine);
+
+        let mut cells = vec![];
+        let mut index = HashMap::new();
+        let mut constraints = vec![];
+
+        for point in size.points() {
+            let Cell { cell_type: CellType::Safe(number), cell_state: CellState::Revealed } = board[point] else {
+                continue
+            };
+
+            let mut vars = vec![];
+            let mut mines = number as isize;
+
+            for neighbour in size.neighbours(point) {
+                if is_unknown(neighbour) {
+                    vars.push(*index.entry(neighbour)
+                            .or_insert_with(|| {
+                                cells.push(neighbour);
+                                cells.len() - 1
+                            }));
+                } else if is_known_mine(neighbour) {
+                    mines -= 1;
+                }
+            }
+
+            if !vars.is_empty() || mines != 0 {
+                constraints.push(Constraint { vars, mines });
+            }
+        }
+
+        let interior = size.points()
+                .filter(|e| is_unknown(*e) && !index.contains_key(e))
+                .collect();
+
+        let ignored_flags = match flags {
+            Flags::Trust => 0,
+            Flags::Ignore => board.iter()
+                    .filter(|e| e.cell_state == CellState::Flagged)
+                    .count() as isize
+        };
+        let revealed_mines = board.iter()
+                .filter(|e| e.cell_type == CellType::Mine && e.cell_state == CellState::Revealed)
+                .count() as isize;
+
+        let components = split_components(cells.len(), &constraints);
+
+        Self {
+            cells,
+            constraints,
+            components,
+            interior,
+            mines: state.remaining_mines + ignored_flags - revealed_mines
+        }
+    }
+
+    pub fn enumerate(&self, component: &Component, budget: usize) -> Option<Tallies> {
+        let Some(mut search) = self.searcher(component, budget) else {
+            return Some(BTreeMap::new())
+        };
+
+        search.search(0)
+                .then_some(search.tallies)
+    }
+
+    // like `enumerate`, but keeps every layout it goes through as well (there can't be more than the budget of them)
+    pub fn find(&self, component: &Component, budget: usize) -> Option<(Tallies, Found)> {
+        let Some(mut search) = self.searcher(component, budget) else {
+            return Some((BTreeMap::new(), BTreeMap::new()))
+        };
+        search.found = Some(BTreeMap::new());
+
+        search.search(0)
+                .then(|| (search.tallies, search.found.unwrap_or_default()))
+    }
+
+    // None if some number can't be satisfied whatever happens
+    fn searcher(&self, component: &Component, budget: usize) -> Option<Search> {
+        let local: HashMap<_, _> = component.vars.iter()
+                .enumerate()
+                .map(|(i, var)| (*var, i))
+                .collect();
+
+        let mut var_constraints = vec![vec![]; component.vars.len()];
+        let mut open = vec![];
+        let mut targets = vec![];
+
+        for (i, constraint) in component.constraints.iter().enumerate() {
+            let constraint = &self.constraints[*constraint];
+
+            for var in &constraint.vars {
+                var_constraints[local[var]].push(i);
+            }
+            open.push(constraint.vars.len() as isize);
+            targets.push(constraint.mines);
+        }
+
+        let search = Search {
+            var_constraints,
+            ones: vec![0; targets.len()],
+            open,
+            targets,
+            assignment: vec![false; component.vars.len()],
+            mines: 0,
+            max_mines: self.mines.max(0) as usize,
+            nodes: 0,
+            budget,
+            tallies: BTreeMap::new(),
+            found: None
+        };
+
+        if search.targets.iter()
+                .zip(&search.open)
+                .any(|(target, open)| *target < 0 || target > open) {
+            return None
+        }
+
+        Some(search)
+    }
+
+    pub fn tallies(&self, budget: usize) -> Vec<Option<Tallies>> {
+        self.components.iter()
+                .map(|e| self.enumerate(e, budget))
+                .collect()
+    }
+
+    pub fn is_contradictory(&self) -> bool {
+        self.mines < 0 || self.constraints.iter()
+                .any(|e| e.vars.is_empty() && e.mines != 0)
+    }
+
+    // which mine totals each component could have once every other component and the interior are accounted for
+    pub fn feasible_totals(&self, tallies: &[Option<Tallies>]) -> Option<Vec<Vec<bool>>> {
+        if self.is_contradictory() {
+            return None
+        }
+
+        let limit = self.mines as usize;
+        let interior = self.interior.len();
+        let fits = |sum: usize| sum <= limit && limit - sum <= interior;
+
+        let totals: Vec<Vec<bool>> = self.components.iter()
+                .zip(tallies)
+                .map(|(component, tallies)| {
+                    let mut totals = vec![false; limit + 1];
+                    match tallies {
+                        Some(tallies) => for total in tallies.keys() {
+                            totals[*total] = true;
+                        }
+                        None => for total in totals.iter_mut().take(component.vars.len() + 1) {
+                            *total = true;
+                        }
+                    }
+                    totals
+                })
+                .collect();
+
+        let mut prefix = vec![single(0, limit)];
+        for totals in &totals {
+            prefix.push(add_sums(prefix.last().unwrap(), totals));
+        }
+        let mut suffix = vec![single(0, limit)];
+        for totals in totals.iter().rev() {
+            suffix.push(add_sums(suffix.last().unwrap(), totals));
+        }
+        suffix.reverse();
+
+        if !prefix.last().unwrap().iter()
+                .enumerate()
+                .any(|(sum, possible)| *possible && fits(sum)) {
+            return None
+        }
+
+        Some(totals.iter()
+                .enumerate()
+                .map(|(i, totals)| {
+                    let others = add_sums(&prefix[i], &suffix[i + 1]);
+                    totals.iter()
+                            .enumerate()
+                            .map(|(total, possible)| *possible && others.iter()
+                                    .enumerate()
+                                    .any(|(sum, other)| *other && fits(total + sum)))
+                            .collect()
+                })
+                .collect())
+    }
+
+    // every mine total the frontier as a whole can have, alongside the interior
+    pub fn frontier_totals(&self, tallies: &[Option<Tallies>]) -> Vec<bool> {
+        let limit = self.mines.max(0) as usize;
+
+        self.feasible_totals(tallies)
+                .map(|totals| totals.iter()
+                        .fold(single(0, limit), |sums, totals| add_sums(&sums, totals)))
+                .unwrap_or_else(|| vec![false; limit + 1])
+                .into_iter()
+                .enumerate()
+                .map(|(sum, possible)| possible && limit - sum <= self.interior.len())
+                .collect()
+    }
+
+    pub fn deduce(&self, budget: usize) -> Deductions {
+        self.deduce_from(&self.tallies(budget))
+    }
+
+    pub fn deduce_from(&self, tallies: &[Option<Tallies>]) -> Deductions {
+        let complete = tallies.iter().all(Option::is_some);
+
+        let Some(feasible) = self.feasible_totals(tallies) else {
+            return Deductions { complete, ..Default::default() }
+        };
+
+        let mut deductions = Deductions { complete, ..Default::default() };
+
+        for ((component, tallies), feasible) in self.components.iter().zip(tallies).zip(&feasible) {
+            let Some(tallies) = tallies else { continue };
+
+            for (i, var) in component.vars.iter().enumerate() {
+                let mut can_be_mine = false;
+                let mut can_be_safe = false;
+
+                for (total, tally) in tallies {
+                    if feasible[*total] {
+                        can_be_mine |= tally.mines[i] > 0;
+                        can_be_safe |= tally.mines[i] < tally.count;
+                    }
+                }
+
+                if !can_be_mine {
+                    deductions.safe.insert(self.cells[*var]);
+                } else if !can_be_safe {
+                    deductions.mines.insert(self.cells[*var]);
+                }
+            }
+        }
+
+        if !self.interior.is_empty() {
+            let limit = self.mines as usize;
+            let sums = self.frontier_totals(tallies);
+            let interior_mines = || sums.iter()
+                    .enumerate()
+                    .filter(|(_, possible)| **possible)
+                    .map(|(sum, _)| limit - sum);
+
+            if interior_mines().all(|e| e == 0) {
+                deductions.safe.extend(self.interior.iter().copied());
+            } else if interior_mines().all(|e| e == self.interior.len()) {
+                deductions.mines.extend(self.interior.iter().copied());
+            }
+        }
+
+        deductions
+    }
+}
+
+struct Search {
+    var_constraints: Vec<Vec<usize>>,
+    ones: Vec<isize>,
+    open: Vec<isize>,
+    targets: Vec<isize>,
+    assignment: Vec<bool>,
+    mines: usize,
+    max_mines: usize,
+    nodes: usize,
+    budget: usize,
+    tallies: Tallies,
+    found: Option<Found>
+}
+
+impl Search {
+    // false if it ran out of budget
+    fn search(&mut self, depth: usize) -> bool {
+        self.nodes += 1;
+        if self.nodes > self.budget {
+            return false
+        }
+
+        if depth == self.assignment.len() {
+            let tally = self.tallies.entry(self.mines)
+                    .or_insert_with(|| Tally { count: 0, mines: vec![0; self.assignment.len()] });
+
+            tally.count += 1;
+            for (mines, assigned) in tally.mines.iter_mut().zip(&self.assignment) {
+                if *assigned {
+                    *mines += 1;
+                }
+            }
+
+            if let Some(found) = &mut self.found {
+                found.entry(self.mines)
+                        .or_default()
+                        .push(self.assignment.clone());
+            }
+            return true
+        }
+
+        for value in [false, true] {
+            if value && self.mines >= self.max_mines {
+                continue
+            }
+
+            let mut fine = true;
+            for constraint in &self.var_constraints[depth] {
+                self.open[*constraint] -= 1;
+                if value {
+                    self.ones[*constraint] += 1;
+                }
+                fine &= self.ones[*constraint] <= self.targets[*constraint]
+                        && self.ones[*constraint] + self.open[*constraint] >= self.targets[*constraint];
+            }
+
+            let mut finished = true;
+            if fine {
+                self.assignment[depth] = value;
+                self.mines += value as usize;
+                finished = self.search(depth + 1);
+                self.mines -= value as usize;
+                self.assignment[depth] = false;
+            }
+
+            for constraint in &self.var_constraints[depth] {
+                self.open[*constraint] += 1;
+                if value {
+                    self.ones[*constraint] -= 1;
+                }
+            }
+
+            if !finished {
+                return false
+            }
+        }
+
+        true
+    }
+}
+
+fn split_components(vars: usize, constraints: &[Constraint]) -> Vec<Component> {
+    let mut var_constraints = vec![vec![]; vars];
+    for (i, constraint) in constraints.iter().enumerate() {
+        for var in &constraint.vars {
+            var_constraints[*var].push(i);
+        }
+    }
+
+    let mut seen_vars = vec![false; vars];
+    let mut seen_constraints = vec![false; constraints.len()];
+    let mut components = vec![];
+
+    for start in 0..vars {
+        if seen_vars[start] {
+            continue
+        }
+
+        // breadth first so that cells next to each other get decided one after another and the search prunes early
+        let mut component = Component { vars: vec![start], constraints: vec![] };
+        seen_vars[start] = true;
+        let mut next = 0;
+
+        while next < component.vars.len() {
+            let var = component.vars[next];
+            next += 1;
+
+            for constraint in &var_constraints[var] {
+                if seen_constraints[*constraint] {
+                    continue
+                }
+                seen_constraints[*constraint] = true;
+                component.constraints.push(*constraint);
+
+                for other in &constraints[*constraint].vars {
+                    if !seen_vars[*other] {
+                        seen_vars[*other] = true;
+                        component.vars.push(*other);
+                    }
+                }
+            }
+        }
+
+        components.push(component);
+    }
+
+    components
+}
+
+fn single(value: usize, limit: usize) -> Vec<bool> {
+    let mut sums = vec![false; limit + 1];
+    if value <= limit {
+        sums[value] = true;
+    }
+    sums
+}
+
+fn add_sums(a: &[bool], b: &[bool]) -> Vec<bool> {
+    let mut sums = vec![false; a.len()];
+
+    for (i, _) in a.iter().enumerate().filter(|(_, e)| **e) {
+        for (j, _) in b.iter().enumerate().filter(|(_, e)| **e) {
+            if i + j < sums.len() {
+                sums[i + j] = true;
+            }
+        }
+    }
+
+    sums
+}
@@ -0,0 +1,165 @@
+use crate::minsweeper::generate_nmbers;
+use crate::solver::frontier::{Flags, Found, Frontier, Tallies};
+use crate::{CellState, CellType, GameState};
+use num_bigint::BigUint;
+use std::collections::HashSet;
+
+// every way the hidden mines could be laid out that fits what the player can see
+// flags are only the player's guesses here too, so flagged cells can go either way
+#[derive(Clone, Debug)]
+pub struct Layouts {
+    state: GameState,
+    frontier: Frontier,
+    tallies: Vec<Tallies>,
+    // each component's layouts, kept from counting them so sampling doesn't have to search again
+    found: Vec<Found>,
+    // ways[i][mines] is how many ways the first i components can hold that many mines between them
+    ways: Vec<Vec<BigUint>>,
+    // how many layouts leave that many mines on the frontier, once the interior's combinations are counted in
+    weights: Vec<BigUint>,
+    count: BigUint
+}
+
+impl Layouts {
+    // None if there are too many layouts around some number to go through them
+    pub fn new(state: &GameState) -> Option<Self> {
+        let frontier = Frontier::new(state, Flags::Ignore);
+        let (tallies, found): (Vec<Tallies>, Vec<Found>) = frontier.components.iter()
+                .map(|e| frontier.find(e, Frontier::SEARCH_BUDGET))
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .unzip();
+
+        let limit = frontier.mines.max(0) as usize;
+        let interior = frontier.interior.len();
+
+        let mut ways = vec![single(limit)];
+        for tallies in &tallies {
+            let previous = ways.last().unwrap();
+            let mut next = vec![BigUint::ZERO; limit + 1];
+
+            for (sum, ways) in previous.iter().enumerate() {
+                for (total, tally) in tallies.range(..=limit - sum) {
+                    next[sum + total] += ways * tally.count;
+                }
+            }
+            ways.push(next);
+        }
+
+        let weights: Vec<BigUint> = match frontier.is_contradictory() {
+            true => vec![BigUint::ZERO; limit + 1],
+            false => ways.last().unwrap().iter()
+                    .enumerate()
+                    .map(|(sum, ways)| match limit - sum <= interior {
+                        true => ways * choose(interior, limit - sum),
+                        false => BigUint::ZERO
+                    })
+                    .collect()
+        };
+        let count = weights.iter().sum();
+
+        Some(Self { state: state.clone(), frontier, tallies, found, ways, weights, count })
+    }
+
+    pub fn count(&self) -> &BigUint {
+        &self.count
+    }
+
+    // every layout is as likely as any other, None if there aren't any
+    // the result has the player's revealed cells and flags where they were, and every number filled in
+    pub fn sample(&self, rng: &mut fastrand::Rng) -> Option<GameState> {
+        if self.count == BigUint::ZERO {
+            return None
+        }
+
+        let mut sum = pick_weighted(rng, self.weights.iter().cloned().enumerate());
+        let mut mines = HashSet::new();
+
+        // going backwards, each component gets a total in proportion to how many ways the ones before it can make up the rest
+        for (i, (component, tallies)) in self.frontier.components.iter().zip(&self.tallies).enumerate().rev() {
+            let total = pick_weighted(rng, tallies.range(..=sum)
+                    .map(|(total, tally)| (*total, &self.ways[i][sum - total] * tally.count)));
+            sum -= total;
+
+            let layouts = &self.found[i][&total];
+            let assignment = &layouts[rng.usize(..layouts.len())];
+
+            mines.extend(component.vars.iter()
+                    .zip(assignment)
+                    .filter(|(_, mine)| **mine)
+                    .map(|(var, _)| self.frontier.cells[*var]));
+        }
+
+        let frontier_mines = mines.len();
+        let mut interior = self.frontier.interior.clone();
+        rng.shuffle(&mut interior);
+        mines.extend(interior.into_iter().take(self.frontier.mines as usize - frontier_mines));
+
+        let mut state = self.state.clone();
+        let size = state.board.size();
+        for point in size.points() {
+            let cell = &mut state.board[point];
+            let revealed_mine = cell.cell_state == CellState::Revealed && cell.cell_type == CellType::Mine;
+
+            cell.cell_type = if revealed_mine || mines.contains(&point) { CellType::Mine } else { CellType::EMPTY };
+        }
+        generate_nmbers(&mut state.board);
+
+        Some(state)
+    }
+}
+
+// None when some component has more layouts than `Frontier::SEARCH_BUDGET` (about a million search steps) lets it go through,
+// rather than a count that's missing some of them
+pub fn count_layouts(state: &GameState) -> Option<BigUint> {
+    Layouts::new(state)
+            .map(|e| e.count)
+}
+
+pub fn sample_layout(state: &GameState, rng: &mut fastrand::Rng) -> Option<GameState> {
+    Layouts::new(state)?
+            .sample(rng)
+}
+
+fn single(limit: usize) -> Vec<BigUint> {
+    let mut ways = vec![BigUint::ZERO; limit + 1];
+    ways[0] = BigUint::from(1u8);
+    ways
+}
+
+fn choose(n: usize, k: usize) -> BigUint {
+    (0..k).fold(BigUint::from(1u8), |ways, i| ways * (n - i) / (i + 1))
+}
+
+fn pick_weighted<T>(rng: &mut fastrand::Rng, options: impl Iterator<Item = (T, BigUint)> + Clone) -> T {
+    let total: BigUint = options.clone()
+            .map(|(_, weight)| weight)
+            .sum();
+    let mut left = random_below(rng, &total);
+
+    for (option, weight) in options {
+        if left < weight {
+            return option
+        }
+        left -= weight;
+    }
+
+    unreachable!("should always land on one of the options")
+}
+
+fn random_below(rng: &mut fastrand::Rng, bound: &BigUint) -> BigUint {
+    let bits = bound.bits();
+    let mask = (BigUint::from(1u8) << bits) - 1u8;
+
+    // with the bits above the bound's masked off at least half the tries land under it
+    loop {
+        let digits: Vec<u32> = (0..bits.div_ceil(32))
+                .map(|_| rng.u32(..))
+                .collect();
+        let value = BigUint::from_slice(&digits) & &mask;
+
+        if value < *bound {
+            return value
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use crate::board::{Board, BoardSize, Point};
 use crate::solver::Operation::{Chord, Flag, Reveal};
+use crate::solver::frontier::{Flags, Frontier};
 use crate::solver::{Action, Actionable, GameResult, Logic, Move, Reason, Solver};
 use crate::{CellState, CellType, GameState, GameStatus, Minsweeper};
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::ops::Sub;
@@ -13,6 +14,7 @@ use enumset::{EnumSet, EnumSetType};
 pub enum Level {
     Beginner,
     Intermediate,
+    Advanced,
     Expert
 }
 
@@ -22,7 +24,8 @@ impl Level {
             Level::Beginner => MiaLogic::Chord | MiaLogic::FlagChord,
             Level::Intermediate => MiaLogic::RegionDeductionReveal | MiaLogic::RegionDeductionFlag
                     | MiaLogic::ZeroMinesRemaining,
-            Level::Expert => MiaLogic::BruteForce | MiaLogic::BruteForceExhaustion,
+            Level::Advanced => MiaLogic::Elimination.into(),
+            Level::Expert => MiaLogic::BruteForce | MiaLogic::BruteForceExhaustion | MiaLogic::MineCount,
         }
     }
 }
@@ -294,6 +297,14 @@ impl MiaSolver {
             }
         }
 
+        if self.skill_level < Level::Advanced {
+            return None
+        }
+
+        if let Some((clicks, related)) = eliminate(&Frontier::new(state, Flags::Trust)) {
+            return Some((Move::multi(clicks, Some(Reason::new(MiaLogic::Elimination, related))), MiaLogic::Elimination))
+        }
+
         if self.skill_level < Level::Expert {
             return None
         }
@@ -354,46 +365,287 @@ impl MiaSolver {
 
         }
 
+        if let Some((clicks, related)) = count_mines(&Frontier::new(state, Flags::Trust)) {
+            return Some((Move::multi(clicks, Some(Reason::new(MiaLogic::MineCount, related))), MiaLogic::MineCount))
+        }
+
         None
     }
 }
 
-impl Solver for MiaSolver {
-
-    fn solve(&self, state: &GameState) -> Option<Move> {
-        self.internal_solve(state)
-                .map(|(e, _)| e)
+impl MiaSolver {
+    // plays the game like `solve_game` does, handing every logic used to `on_move` before its move is made
+    // returning false from `on_move` gives up on the game there and then
+    pub fn play_game(&self, minsweeper: &mut dyn Minsweeper, mut on_move: impl FnMut(MiaLogic) -> bool) -> GameResult {
+        self.play(minsweeper, |logic, _, _| on_move(logic))
     }
 
-    fn solve_game(&self, minsweeper: &mut dyn Minsweeper) -> GameResult {
-        let mut requirement_met = self.required_level.is_none();
-        let required_logic = self.required_level
-                .map(Level::logics)
-                .unwrap_or_default();
+    // the loop all of these play through, `on_move` also sees the move and the state it gets made on
+    fn play(&self, minsweeper: &mut dyn Minsweeper, mut on_move: impl FnMut(MiaLogic, &Move, &GameState) -> bool) -> GameResult {
         let mut state = minsweeper.gamestate();
 
         while state.status == GameStatus::Playing {
-            let Some((Move { actions, ..}, logic)) = self.internal_solve(state) else { break };
+            let Some((play, logic)) = self.internal_solve(state) else { break };
 
-            if !requirement_met && required_logic.contains(logic) {
-                requirement_met = true;
+            if !on_move(logic, &play, state) {
+                return GameResult::Resigned
             }
 
-            for action in actions {
+            for action in play.actions {
                 state = minsweeper.action(action).into()
             }
         }
 
         match state.status {
-            GameStatus::Won if requirement_met => GameResult::Won,
+            GameStatus::Won => GameResult::Won,
             GameStatus::Lost => GameResult::Lost,
-            GameStatus::Playing => GameResult::Resigned,
             _ => GameResult::Resigned
         }
+    }
+
+    pub fn solve_game_counted(&self, minsweeper: &mut dyn Minsweeper) -> (GameResult, LogicCounts) {
+        let mut counts = LogicCounts::default();
+
+        let result = self.play_game(minsweeper, |logic| {
+            counts.record(logic);
+            true
+        });
 
+        (result, counts)
+    }
+
+    // whether a game played with these counts used the level `only` asked for
+    pub(crate) fn meets_requirement(&self, counts: &LogicCounts) -> bool {
+        self.required_level
+                .is_none_or(|level| !level.logics().is_disjoint(counts.used()))
+    }
+
+    pub fn solve_game_traced(&self, minsweeper: &mut dyn Minsweeper) -> Trace {
+        let mut steps = vec![];
+        let mut counts = LogicCounts::default();
+
+        let result = self.play(minsweeper, |logic, play, before| {
+            counts.record(logic);
+            steps.push(Step {
+                actions: play.actions.iter().copied().collect(),
+                logic,
+                related: play.reason.as_ref().map(|e| e.related.clone()).unwrap_or_default(),
+                before: before.clone(),
+                after: before.clone()
+            });
+            true
+        });
+
+        // nothing happens between moves, so each step ends where the next one starts
+        let mut after = minsweeper.gamestate().clone();
+        for step in steps.iter_mut().rev() {
+            step.after = std::mem::replace(&mut after, step.before.clone());
+        }
+
+        Trace { result, steps, counts }
     }
 }
 
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trace {
+    pub result: GameResult,
+    pub steps: Vec<Step>,
+    pub counts: LogicCounts
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Step {
+    pub actions: Vec<Action>,
+    pub logic: MiaLogic,
+    pub related: HashSet<Point>,
+    pub before: GameState,
+    pub after: GameState
+}
+
+impl Solver for MiaSolver {
+
+    fn solve(&self, state: &GameState) -> Option<Move> {
+        self.internal_solve(state)
+                .map(|(e, _)| e)
+    }
+
+    fn solve_game(&self, minsweeper: &mut dyn Minsweeper) -> GameResult {
+        let (result, counts) = self.solve_game_counted(minsweeper);
+
+        match result {
+            GameResult::Won if self.meets_requirement(&counts) => GameResult::Won,
+            GameResult::Lost => GameResult::Lost,
+            _ => GameResult::Resigned
+        }
+
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogicCounts {
+    counts: HashMap<MiaLogic, usize>
+}
+
+impl LogicCounts {
+    pub fn get(&self, logic: MiaLogic) -> usize {
+        self.counts.get(&logic)
+                .copied()
+                .unwrap_or(0)
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn used(&self) -> EnumSet<MiaLogic> {
+        self.counts.keys()
+                .copied()
+                .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (MiaLogic, usize)> {
+        self.counts.iter()
+                .map(|(logic, count)| (*logic, *count))
+    }
+
+    pub fn record(&mut self, logic: MiaLogic) {
+        *self.counts.entry(logic).or_default() += 1;
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (logic, count) in other.iter() {
+            *self.counts.entry(logic).or_default() += count;
+        }
+    }
+}
+
+// row reduces every number's constraint at once, then any row whose right hand side sits at the very edge of
+// what its 0/1 cells can add up to forces all of them
+fn eliminate(frontier: &Frontier) -> Option<(HashSet<Action>, HashSet<Point>)> {
+    let mut clicks = HashSet::new();
+    let mut related = HashSet::new();
+
+    for component in &frontier.components {
+        let columns: HashMap<_, _> = component.vars.iter()
+                .enumerate()
+                .map(|(i, var)| (*var, i))
+                .collect();
+
+        let mut rows: Vec<Vec<i128>> = component.constraints.iter()
+                .map(|e| {
+                    let constraint = &frontier.constraints[*e];
+                    let mut row = vec![0; columns.len() + 1];
+                    for var in &constraint.vars {
+                        row[columns[var]] = 1;
+                    }
+                    row[columns.len()] = constraint.mines as i128;
+                    row
+                })
+                .collect();
+
+        let mut pivot_row = 0;
+        for column in 0..columns.len() {
+            let Some(pivot) = (pivot_row..rows.len()).find(|e| rows[*e][column] != 0) else { continue };
+            rows.swap(pivot_row, pivot);
+
+            for i in 0..rows.len() {
+                if i == pivot_row || rows[i][column] == 0 {
+                    continue
+                }
+
+                let (factor, pivot_factor) = (rows[pivot_row][column], rows[i][column]);
+                let reduced: Vec<_> = rows[i].iter()
+                        .zip(&rows[pivot_row])
+                        .map(|(a, b)| a * factor - b * pivot_factor)
+                        .collect();
+                rows[i] = normalise(reduced);
+            }
+
+            pivot_row += 1;
+        }
+
+        for row in &rows {
+            let (coefficients, total) = row.split_at(columns.len());
+            let total = total[0];
+
+            let most: i128 = coefficients.iter().filter(|e| **e > 0).sum();
+            let least: i128 = coefficients.iter().filter(|e| **e < 0).sum();
+
+            // at the top every positive cell is a mine and every negative one is safe, and the other way around at the bottom
+            let positive_mines = if total == most {
+                true
+            } else if total == least {
+                false
+            } else {
+                continue
+            };
+
+            for (coefficient, var) in coefficients.iter().zip(&component.vars) {
+                if *coefficient == 0 {
+                    continue
+                }
+
+                let point = frontier.cells[*var];
+                let operation = if (*coefficient > 0) == positive_mines { Flag } else { Reveal };
+
+                clicks.insert(Action::new(point, operation));
+                related.insert(point);
+            }
+        }
+    }
+
+    (!clicks.is_empty()).then_some((clicks, related))
+}
+
+// only keeps what needs the remaining mine count, anything the numbers decide on their own is brute force's job
+fn count_mines(frontier: &Frontier) -> Option<(HashSet<Action>, HashSet<Point>)> {
+    let tallies = frontier.tallies(Frontier::SEARCH_BUDGET);
+    let deductions = frontier.deduce_from(&tallies);
+
+    let mut decided = HashSet::new();
+    for (component, tallies) in frontier.components.iter().zip(&tallies) {
+        let Some(tallies) = tallies else { continue };
+
+        for (i, var) in component.vars.iter().enumerate() {
+            if tallies.values().all(|e| e.mines[i] == 0) || tallies.values().all(|e| e.mines[i] == e.count) {
+                decided.insert(frontier.cells[*var]);
+            }
+        }
+    }
+
+    let clicks: HashSet<_> = deductions.safe.iter()
+            .filter(|e| !decided.contains(e))
+            .map(|e| Action::new(*e, Reveal))
+            .chain(deductions.mines.iter()
+                    .filter(|e| !decided.contains(e))
+                    .map(|e| Action::new(*e, Flag)))
+            .collect();
+
+    if clicks.is_empty() {
+        return None
+    }
+
+    Some((clicks, frontier.cells.iter().chain(&frontier.interior).copied().collect()))
+}
+
+fn normalise(row: Vec<i128>) -> Vec<i128> {
+    let divisor = row.iter()
+            .fold(0, |a, b| gcd(a, b.abs()));
+
+    if divisor > 1 {
+        row.into_iter().map(|e| e / divisor).collect()
+    } else {
+        row
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
 struct BruteForceResult {
     solve: bool,
     always_flag: HashSet<Point>,
@@ -440,6 +692,10 @@ fn brute_force_3(empties: &HashSet<Point>, adjacents: &Vec<Point>, adjacent_inde
     if n < flagged as u8 {
         return
     }
+    // without this whether a layout with too many mines gets counted depends on what order the numbers are visited in
+    if (n as usize - flagged) as isize > state.remaining_mines {
+        return
+    }
     let flaggable = state.board.size()
             .neighbours(adjacent)
             .filter(|point| state.board[*point].cell_state == CellState::Unknown)
@@ -447,6 +703,7 @@ fn brute_force_3(empties: &HashSet<Point>, adjacents: &Vec<Point>, adjacent_inde
     recurse_flag(empties, adjacents, adjacent_index, &flaggable, 0, n as usize - flagged, state, result);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn recurse_flag(empties: &HashSet<Point>, adjacents: &Vec<Point>, adjacent_index: usize, flaggable: &Vec<Point>, start: usize, mines_to_flag: usize, state: &mut GameState, result: &mut BruteForceResult) {
 
     if mines_to_flag == 0 {
@@ -653,15 +910,18 @@ fn simulate_unreveal(state: &mut GameState, point: Point) {
 }
 
 
-#[derive(EnumSetType, Debug)]
+#[derive(EnumSetType, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MiaLogic {
     Chord,
     FlagChord,
     RegionDeductionReveal,
     RegionDeductionFlag,
     ZeroMinesRemaining,
+    Elimination,
     BruteForce,
     BruteForceExhaustion,
+    MineCount,
 }
 
 impl Display for MiaLogic {
@@ -672,8 +932,10 @@ impl Display for MiaLogic {
             MiaLogic::RegionDeductionReveal => write!(f, "the surrounding cells force the cells to be safe"),
             MiaLogic::RegionDeductionFlag => write!(f, "the surrounding cells force the cells to be a mine"),
             MiaLogic::ZeroMinesRemaining => write!(f, "0 mines remaining, all unknown cells must be safe"),
+            MiaLogic::Elimination => write!(f, "adding and subtracting the surrounding numbers leaves a total the cells can only reach one way"),
             MiaLogic::BruteForce => write!(f, "in every possible mine configuration the cells are safe/mines"),
-            MiaLogic::BruteForceExhaustion => write!(f, "in every possible mine configuration every mine is determined, all unused cells must be safe")
+            MiaLogic::BruteForceExhaustion => write!(f, "in every possible mine configuration every mine is determined, all unused cells must be safe"),
+            MiaLogic::MineCount => write!(f, "the remaining mines can only be split between the numbered cells and the rest one way")
         }
     }
 }
@@ -1,5 +1,10 @@
+pub mod analysis;
+mod frontier;
+pub mod layouts;
 pub mod mia;
+pub mod sat;
 pub mod start;
+pub mod target;
 
 use std::collections::HashSet;
 use std::fmt::{Debug, Display};
@@ -26,8 +31,7 @@ pub trait Solver {
         match state.status {
             GameStatus::Won => GameResult::Won,
             GameStatus::Lost => GameResult::Lost,
-            GameStatus::Playing => GameResult::Resigned,
-            _ => unreachable!()
+            GameStatus::NotStarted | GameStatus::AwaitingFirstClick | GameStatus::Playing => GameResult::Resigned
         }
     }
 }
@@ -89,6 +93,7 @@ impl Move {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Action {
     pub point: Point,
     pub operation: Operation,
@@ -101,6 +106,7 @@ impl Action {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Operation {
     Reveal,
     Chord,
@@ -138,6 +144,128 @@ pub trait Logic: Debug + Display {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameResult {
     Won, Lost, Resigned
-}
\ No newline at end of file
+}
+#[cfg(feature = "async")]
+pub mod nonblocking {
+    use crate::minsweeper::nonblocking::AsyncMinsweeperGame;
+    use crate::solver::{Action, GameResult, Move, Operation, Solver};
+    #[cfg(feature = "tokio")]
+    use crate::solver::{Logic, Reason};
+    use crate::{GameState, GameStatus};
+    #[cfg(feature = "tokio")]
+    use std::fmt::{Debug, Display, Formatter};
+
+    // for bots playing on a game other people are playing on too,
+    // every move is worked out from a copy of the board so nobody's kept waiting on the lock while it thinks
+    pub trait AsyncSolver: Send + Sync {
+
+        fn solve_async(&self, game_state: &GameState) -> impl Future<Output = Option<Move>> + Send;
+
+        // `on_move` hears about every move once it's been played
+        // dropping the future stops it between moves, leaving the game as it was for everyone else
+        fn solve_game_async<S, OnWin, OnLose>(&self, game: &AsyncMinsweeperGame<S, OnWin, OnLose>,
+                                              mut on_move: impl FnMut(&[Action], &GameState) + Send) -> impl Future<Output = GameResult> + Send
+        where
+            S: Solver + Send + Sync + Clone + 'static,
+            OnWin: Fn() + Send + Sync,
+            OnLose: Fn() + Send + Sync
+        {
+            async move {
+                let mut state = game.gamestate().await;
+
+                while state.status == GameStatus::Playing {
+                    let Some(actions) = self.solve_async(&state).await
+                            .map(|e| e.actions.into_iter().collect::<Vec<_>>()) else { break };
+
+                    let mut moved = false;
+                    for action in &actions {
+                        // someone else might have flagged it in the meantime, and toggling would take their flag away
+                        let result = match action.operation {
+                            Operation::Reveal => game.reveal(action.point).await,
+                            Operation::Chord => game.clear_around(action.point).await,
+                            Operation::Flag => game.set_flagged(action.point, true).await
+                        };
+                        moved |= result.is_ok();
+                    }
+
+                    let next = game.gamestate().await;
+                    // the whole move was already made by someone else, unless nothing changed at all and it's stuck
+                    if !moved && next == state {
+                        break
+                    }
+                    if moved {
+                        on_move(&actions, &next);
+                    }
+                    state = next;
+                }
+
+                match state.status {
+                    GameStatus::Won => GameResult::Won,
+                    GameStatus::Lost => GameResult::Lost,
+                    _ => GameResult::Resigned
+                }
+            }
+        }
+    }
+
+    // with tokio sync solvers think on the blocking pool, so a long brute force doesn't hold up the runtime
+    // that takes a copy of the solver, so ones that can't be cloned cheaply want to go in an `Arc`
+    #[cfg(feature = "tokio")]
+    impl<T: Solver + Clone + Send + Sync + 'static> AsyncSolver for T {
+        async fn solve_async(&self, game_state: &GameState) -> Option<Move> {
+            let (solver, game_state) = (self.clone(), game_state.clone());
+
+            // logic doesn't have to be able to cross threads, so only what it says about itself comes back
+            let solve = tokio::task::spawn_blocking(move || solver.solve(&game_state)
+                    .map(|Move { actions, reason }| (actions, reason.map(|e| (format!("{:?}", e.logic), e.logic.to_string(), e.related)))));
+
+            match solve.await {
+                Ok(solve) => solve.map(|(actions, reason)| Move {
+                    actions,
+                    reason: reason.map(|(debug, display, related)| Reason::new(Explained { debug, display }, related))
+                }),
+                Err(error) => match error.try_into_panic() {
+                    Ok(panic) => std::panic::resume_unwind(panic),
+                    Err(_) => None
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    struct Explained {
+        debug: String,
+        display: String
+    }
+
+    #[cfg(feature = "tokio")]
+    impl Debug for Explained {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.debug)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl Display for Explained {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.display)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl Logic for Explained {
+
+    }
+
+    // without it there's nowhere else to send them, so they think right there on the task
+    // (the bounds are the same as with tokio, so turning it on somewhere else can't break anything here)
+    #[cfg(not(feature = "tokio"))]
+    impl<T: Solver + Clone + Send + Sync + 'static> AsyncSolver for T {
+        async fn solve_async(&self, game_state: &GameState) -> Option<Move> {
+            self.solve(game_state)
+        }
+    }
+}
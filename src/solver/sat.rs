@@ -0,0 +1,464 @@
+use crate::solver::frontier::{Flags, Frontier};
+use crate::solver::Operation::{Flag, Reveal};
+use crate::solver::{Action, Logic, Move, Reason, Solver};
+use crate::{GameState, GameStatus};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+// unlike `MiaSolver` this never gives up because the frontier is too big, every unknown cell next to a number
+// becomes a 0/1 variable and each number (plus the total mine count) becomes a cardinality constraint over them,
+// which then gets handed to a small conflict driven clause learning solver
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SatSolver;
+
+impl Solver for SatSolver {
+
+    fn solve(&self, state: &GameState) -> Option<Move> {
+        if state.status != GameStatus::Playing {
+            return None
+        }
+
+        let frontier = Frontier::new(state, Flags::Trust);
+        if frontier.is_contradictory() {
+            return None
+        }
+
+        let mines = frontier.mines as usize;
+        let interior = frontier.interior.len();
+
+        let mut sat = Sat::new(frontier.cells.len());
+        for constraint in &frontier.constraints {
+            sat.add_card(constraint.vars.clone(), constraint.mines as usize, constraint.mines as usize);
+        }
+        let everything = sat.add_card((0..frontier.cells.len()).collect(), mines.saturating_sub(interior), mines);
+
+        let model = sat.solve(&[])?;
+
+        let mut seen_mine: Vec<_> = model.clone();
+        let mut seen_safe: Vec<_> = model.iter().map(|e| !e).collect();
+
+        for var in 0..frontier.cells.len() {
+            if seen_mine[var] && seen_safe[var] {
+                continue
+            }
+
+            if let Some(other) = sat.solve(&[lit(var, !model[var])]) {
+                for (var, mine) in other.into_iter().enumerate() {
+                    seen_mine[var] |= mine;
+                    seen_safe[var] |= !mine;
+                }
+            }
+        }
+
+        let mut actions: HashSet<_> = (0..frontier.cells.len())
+                .filter_map(|var| match (seen_mine[var], seen_safe[var]) {
+                    (false, true) => Some(Action::new(frontier.cells[var], Reveal)),
+                    (true, false) => Some(Action::new(frontier.cells[var], Flag)),
+                    _ => None
+                })
+                .collect();
+
+        if !actions.is_empty() {
+            return Some(Move::multi(actions, Some(Reason::new(SatLogic::Frontier, frontier.cells.iter().copied().collect()))))
+        }
+
+        if interior == 0 {
+            return None
+        }
+
+        // the interior is only ever decided by how many mines the frontier can take up
+        let mut fewer = sat.clone();
+        fewer.tighten(everything, mines.saturating_sub(interior), mines.saturating_sub(1));
+        let mut more = sat;
+        more.tighten(everything, (mines + 1).saturating_sub(interior), mines);
+
+        let operation = if mines == 0 || fewer.solve(&[]).is_none() {
+            Reveal
+        } else if more.solve(&[]).is_none() {
+            Flag
+        } else {
+            return None
+        };
+
+        actions.extend(frontier.interior.iter().map(|e| Action::new(*e, operation)));
+
+        Some(Move::multi(actions, Some(Reason::new(SatLogic::MineCount, frontier.cells.iter().copied().collect()))))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SatLogic {
+    Frontier,
+    MineCount
+}
+
+impl Display for SatLogic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SatLogic::Frontier => write!(f, "in every layout that satisfies the numbers and the mine count the cells are safe/mines"),
+            SatLogic::MineCount => write!(f, "the numbers fix how many mines are left over for the cells away from them")
+        }
+    }
+}
+
+impl Logic for SatLogic {
+
+}
+
+// literals are a variable and the value it's being set to packed together
+type Lit = usize;
+
+const fn lit(var: usize, value: bool) -> Lit {
+    var << 1 | value as usize
+}
+
+const fn var(lit: Lit) -> usize {
+    lit >> 1
+}
+
+const fn value(lit: Lit) -> bool {
+    lit & 1 == 1
+}
+
+const fn negate(lit: Lit) -> Lit {
+    lit ^ 1
+}
+
+#[derive(Clone, Debug)]
+struct Card {
+    vars: Vec<usize>,
+    min: usize,
+    max: usize
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Antecedent {
+    Decision,
+    Card(usize),
+    Clause(usize)
+}
+
+#[derive(Clone, Debug)]
+struct Sat {
+    cards: Vec<Card>,
+    clauses: Vec<Vec<Lit>>,
+    var_cards: Vec<Vec<usize>>,
+    var_clauses: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    positions: Vec<usize>,
+    antecedents: Vec<Antecedent>,
+    activity: Vec<f64>,
+    bump: f64,
+    trail: Vec<Lit>,
+    trail_limits: Vec<usize>,
+    propagated: usize,
+    unchecked: Vec<usize>,
+    unsatisfiable: bool
+}
+
+impl Sat {
+    pub fn new(vars: usize) -> Self {
+        Self {
+            cards: vec![],
+            clauses: vec![],
+            var_cards: vec![vec![]; vars],
+            var_clauses: vec![vec![]; vars],
+            values: vec![None; vars],
+            levels: vec![0; vars],
+            positions: vec![0; vars],
+            antecedents: vec![Antecedent::Decision; vars],
+            activity: vec![0.0; vars],
+            bump: 1.0,
+            trail: vec![],
+            trail_limits: vec![],
+            propagated: 0,
+            unchecked: vec![],
+            unsatisfiable: false
+        }
+    }
+
+    pub fn add_card(&mut self, vars: Vec<usize>, min: usize, max: usize) -> usize {
+        let index = self.cards.len();
+
+        for var in &vars {
+            self.var_cards[*var].push(index);
+        }
+        self.cards.push(Card { vars, min, max });
+        self.unchecked.push(index);
+
+        index
+    }
+
+    // only ever safe to make a constraint stricter, anything learnt so far still holds afterwards
+    pub fn tighten(&mut self, card: usize, min: usize, max: usize) {
+        self.cards[card].min = self.cards[card].min.max(min);
+        self.cards[card].max = self.cards[card].max.min(max);
+        self.unchecked.push(card);
+    }
+
+    // finds a model where every assumption holds, if there is one
+    pub fn solve(&mut self, assumptions: &[Lit]) -> Option<Vec<bool>> {
+        self.backtrack(0);
+
+        while let Some(card) = self.unchecked.pop() {
+            if self.check_card(card).is_some() {
+                self.unsatisfiable = true;
+            }
+        }
+
+        loop {
+            if self.unsatisfiable {
+                return None
+            }
+
+            if let Some(conflict) = self.propagate() {
+                if self.level() == 0 {
+                    self.unsatisfiable = true;
+                    return None
+                }
+
+                let (learnt, level) = self.analyse(conflict);
+                self.backtrack(level);
+                self.learn(learnt);
+                continue
+            }
+
+            if let Some(assumption) = assumptions.get(self.level()) {
+                match self.values[var(*assumption)] {
+                    Some(value) if value != self::value(*assumption) => {
+                        self.backtrack(0);
+                        return None
+                    }
+                    Some(_) => self.trail_limits.push(self.trail.len()),
+                    None => {
+                        self.trail_limits.push(self.trail.len());
+                        self.assign(*assumption, Antecedent::Decision);
+                    }
+                }
+                continue
+            }
+
+            let next = (0..self.values.len())
+                    .filter(|e| self.values[*e].is_none())
+                    .max_by(|a, b| self.activity[*a].total_cmp(&self.activity[*b]));
+
+            let Some(next) = next else {
+                let model = self.values.iter()
+                        .map(|e| e.unwrap())
+                        .collect();
+                self.backtrack(0);
+                return Some(model)
+            };
+
+            // mines are rarer than safe cells, so guessing safe first finds models sooner
+            self.trail_limits.push(self.trail.len());
+            self.assign(lit(next, false), Antecedent::Decision);
+        }
+    }
+
+    fn level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    fn assign(&mut self, lit: Lit, antecedent: Antecedent) {
+        let var = var(lit);
+
+        self.values[var] = Some(value(lit));
+        self.levels[var] = self.level();
+        self.positions[var] = self.trail.len();
+        self.antecedents[var] = antecedent;
+        self.trail.push(lit);
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if level >= self.level() {
+            return
+        }
+
+        let limit = self.trail_limits[level];
+        for lit in self.trail.drain(limit..) {
+            self.values[var(lit)] = None;
+        }
+        self.trail_limits.truncate(level);
+        self.propagated = self.propagated.min(self.trail.len());
+    }
+
+    fn is_true(&self, lit: Lit) -> bool {
+        self.values[var(lit)] == Some(value(lit))
+    }
+
+    fn is_false(&self, lit: Lit) -> bool {
+        self.values[var(lit)] == Some(!value(lit))
+    }
+
+    // returns the literals that are currently true and can't all be at once
+    fn propagate(&mut self) -> Option<Vec<Lit>> {
+        while self.propagated < self.trail.len() {
+            let var = var(self.trail[self.propagated]);
+            self.propagated += 1;
+
+            for i in 0..self.var_cards[var].len() {
+                if let Some(conflict) = self.check_card(self.var_cards[var][i]) {
+                    return Some(conflict)
+                }
+            }
+
+            for i in 0..self.var_clauses[var].len() {
+                if let Some(conflict) = self.check_clause(self.var_clauses[var][i]) {
+                    return Some(conflict)
+                }
+            }
+        }
+
+        None
+    }
+
+    fn check_card(&mut self, index: usize) -> Option<Vec<Lit>> {
+        let card = &self.cards[index];
+
+        let ones = card.vars.iter()
+                .filter(|e| self.values[**e] == Some(true))
+                .count();
+        let zeros = card.vars.iter()
+                .filter(|e| self.values[**e] == Some(false))
+                .count();
+
+        let (forced, conflict) = if ones > card.max {
+            (None, Some(true))
+        } else if card.vars.len() - zeros < card.min {
+            (None, Some(false))
+        } else if ones == card.max {
+            (Some(false), None)
+        } else if card.vars.len() - zeros == card.min {
+            (Some(true), None)
+        } else {
+            (None, None)
+        };
+
+        if let Some(value) = conflict {
+            return Some(card.vars.iter()
+                    .filter(|e| self.values[**e] == Some(value))
+                    .map(|e| lit(*e, value))
+                    .collect())
+        }
+
+        if let Some(value) = forced {
+            let unassigned: Vec<_> = card.vars.iter()
+                    .copied()
+                    .filter(|e| self.values[*e].is_none())
+                    .collect();
+
+            for var in unassigned {
+                self.assign(lit(var, value), Antecedent::Card(index));
+            }
+        }
+
+        None
+    }
+
+    fn check_clause(&mut self, index: usize) -> Option<Vec<Lit>> {
+        let clause = &self.clauses[index];
+
+        if clause.iter().any(|e| self.is_true(*e)) {
+            return None
+        }
+
+        let mut open = clause.iter().filter(|e| !self.is_false(**e));
+
+        match (open.next().copied(), open.next()) {
+            (None, _) => Some(clause.iter().map(|e| negate(*e)).collect()),
+            (Some(unit), None) => {
+                self.assign(unit, Antecedent::Clause(index));
+                None
+            }
+            _ => None
+        }
+    }
+
+    // the true literals that forced `var` to take its value
+    fn explain(&self, var: usize) -> Vec<Lit> {
+        match self.antecedents[var] {
+            Antecedent::Decision => vec![],
+            Antecedent::Clause(index) => self.clauses[index].iter()
+                    .filter(|e| self::var(**e) != var)
+                    .map(|e| negate(*e))
+                    .collect(),
+            Antecedent::Card(index) => {
+                // set to 0 because the card was already full of mines, or set to 1 because it had run out of room
+                let cause = !self.values[var].unwrap();
+
+                self.cards[index].vars.iter()
+                        .filter(|e| self.values[**e] == Some(cause) && self.positions[**e] < self.positions[var])
+                        .map(|e| lit(*e, cause))
+                        .collect()
+            }
+        }
+    }
+
+    // first unique implication point, gives back the learnt clause with the asserting literal last
+    fn analyse(&mut self, conflict: Vec<Lit>) -> (Vec<Lit>, usize) {
+        let level = self.level();
+        let mut seen = vec![false; self.values.len()];
+        let mut learnt = vec![];
+        let mut pending = 0;
+        let mut reason = conflict;
+        let mut index = self.trail.len();
+
+        loop {
+            for lit in reason {
+                let var = var(lit);
+                if seen[var] || self.levels[var] == 0 {
+                    continue
+                }
+
+                seen[var] = true;
+                self.activity[var] += self.bump;
+
+                if self.levels[var] == level {
+                    pending += 1;
+                } else {
+                    learnt.push(negate(lit));
+                }
+            }
+
+            let lit = loop {
+                index -= 1;
+                if seen[var(self.trail[index])] {
+                    break self.trail[index]
+                }
+            };
+            seen[var(lit)] = false;
+            pending -= 1;
+
+            if pending == 0 {
+                learnt.push(negate(lit));
+                break
+            }
+
+            reason = self.explain(var(lit));
+        }
+
+        self.bump *= 1.05;
+
+        let backjump = learnt[..learnt.len() - 1].iter()
+                .map(|e| self.levels[var(*e)])
+                .max()
+                .unwrap_or(0);
+
+        (learnt, backjump)
+    }
+
+    fn learn(&mut self, learnt: Vec<Lit>) {
+        let asserting = *learnt.last().unwrap();
+        let index = self.clauses.len();
+
+        for lit in &learnt {
+            self.var_clauses[var(*lit)].push(index);
+        }
+        self.clauses.push(learnt);
+
+        self.assign(asserting, Antecedent::Clause(index));
+    }
+}
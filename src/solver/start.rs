@@ -15,7 +15,7 @@ impl Solver for SafeStart {
         match minsweeper.gamestate().status {
             GameStatus::Playing | GameStatus::Won => GameResult::Won,
             GameStatus::Lost => GameResult::Lost,
-            GameStatus::Never => GameResult::Resigned
+            GameStatus::NotStarted | GameStatus::AwaitingFirstClick => GameResult::Resigned
         }
     }
 }
@@ -37,7 +37,7 @@ impl Solver for ZeroStart {
             GameStatus::Playing => GameResult::Lost,
             GameStatus::Won => GameResult::Won,
             GameStatus::Lost => GameResult::Lost,
-            GameStatus::Never => GameResult::Resigned
+            GameStatus::NotStarted | GameStatus::AwaitingFirstClick => GameResult::Resigned
         }
     }
 }
@@ -55,7 +55,7 @@ impl Solver for WinStart {
         match minsweeper.gamestate().status {
             GameStatus::Won => GameResult::Won,
             GameStatus::Lost => GameResult::Lost,
-            GameStatus::Playing | GameStatus::Never => GameResult::Resigned
+            GameStatus::NotStarted | GameStatus::AwaitingFirstClick | GameStatus::Playing => GameResult::Resigned
         }
     }
 }
@@ -0,0 +1,114 @@
+use crate::solver::mia::{LogicCounts, MiaLogic, MiaSolver};
+use crate::solver::{GameResult, Move, Solver};
+use crate::{GameState, Minsweeper};
+use enumset::EnumSet;
+use std::collections::HashMap;
+use std::ops::{Bound, RangeBounds};
+
+// a `Solver` that only counts a game as won if the way it was won meets every requirement,
+// so it can be handed straight to the generators to get boards of a particular difficulty
+#[derive(Clone, Debug)]
+pub struct Target {
+    solver: MiaSolver,
+    allowed: EnumSet<MiaLogic>,
+    at_least: HashMap<MiaLogic, usize>,
+    at_most: HashMap<MiaLogic, usize>,
+    bbbv: (Bound<usize>, Bound<usize>),
+    openings: (Bound<usize>, Bound<usize>)
+}
+
+impl Target {
+    pub fn new(solver: MiaSolver) -> Self {
+        Self {
+            solver,
+            allowed: EnumSet::all(),
+            at_least: HashMap::new(),
+            at_most: HashMap::new(),
+            bbbv: (Bound::Unbounded, Bound::Unbounded),
+            openings: (Bound::Unbounded, Bound::Unbounded)
+        }
+    }
+
+    pub fn allow(mut self, logics: impl Into<EnumSet<MiaLogic>>) -> Self {
+        self.allowed = logics.into();
+        self
+    }
+
+    pub fn at_least(mut self, logic: MiaLogic, count: usize) -> Self {
+        self.at_least.insert(logic, count);
+        self
+    }
+
+    pub fn at_most(mut self, logic: MiaLogic, count: usize) -> Self {
+        self.at_most.insert(logic, count);
+        self
+    }
+
+    pub fn bbbv(mut self, range: impl RangeBounds<usize>) -> Self {
+        self.bbbv = bounds(range);
+        self
+    }
+
+    pub fn openings(mut self, range: impl RangeBounds<usize>) -> Self {
+        self.openings = bounds(range);
+        self
+    }
+
+    // a solver made with `MiaSolver::only` also needs its level to have come up somewhere
+    pub fn accepts(&self, counts: &LogicCounts) -> bool {
+        self.solver.meets_requirement(counts)
+                && counts.used().is_subset(self.allowed)
+                && self.at_least.iter()
+                        .all(|(logic, count)| counts.get(*logic) >= *count)
+                && self.at_most.iter()
+                        .all(|(logic, count)| counts.get(*logic) <= *count)
+    }
+
+    fn can_still_accept(&self, counts: &LogicCounts) -> bool {
+        counts.used().is_subset(self.allowed)
+                && self.at_most.iter()
+                        .all(|(logic, count)| counts.get(*logic) <= *count)
+    }
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Self::new(MiaSolver::default())
+    }
+}
+
+// kept as they were given, so an empty range like `..0` stays empty
+fn bounds(range: impl RangeBounds<usize>) -> (Bound<usize>, Bound<usize>) {
+    (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+impl Solver for Target {
+
+    fn solve(&self, game_state: &GameState) -> Option<Move> {
+        self.solver.solve(game_state)
+    }
+
+    fn solve_game(&self, minsweeper: &mut dyn Minsweeper) -> GameResult {
+        let mut counts = LogicCounts::default();
+
+        let result = self.solver.play_game(minsweeper, |logic| {
+            counts.record(logic);
+            self.can_still_accept(&counts)
+        });
+
+        if result != GameResult::Won {
+            return result
+        }
+
+        // once the game is won every safe cell is showing its number so these can be read off the board
+        let board = &minsweeper.gamestate().board;
+
+        if self.accepts(&counts)
+                && self.bbbv.contains(&board.bbbv())
+                && self.openings.contains(&board.openings()) {
+            GameResult::Won
+        } else {
+            GameResult::Resigned
+        }
+    }
+}